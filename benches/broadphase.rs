@@ -0,0 +1,68 @@
+use hex::{nalgebra::Vector2, Id};
+use hex_physics::{
+    aabb::Aabb,
+    broadphase::{AabbTree, Broadphase, BruteForce, SpatialHash, SweepAndPrune},
+};
+use std::time::Instant;
+
+const STEPS: u32 = 20;
+
+fn proxies(count: usize) -> Vec<(Id, Aabb)> {
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+
+        (seed % 10_000) as f32 / 10_000.0
+    };
+    let size = (count as f32).sqrt() * 4.0;
+
+    (0..count)
+        .map(|i| {
+            let min = Vector2::new(random(), random()) * size;
+            let extents = Vector2::new(random(), random()) * 1.5 + Vector2::repeat(0.5);
+
+            (i, Aabb::new(min, min + extents))
+        })
+        .collect()
+}
+
+fn bench(name: &str, broadphase: &mut dyn Broadphase, proxies: &[(Id, Aabb)]) -> usize {
+    let mut pairs = 0;
+    let now = Instant::now();
+
+    for _ in 0..STEPS {
+        broadphase.update(proxies);
+        pairs = broadphase.pairs().len();
+    }
+
+    println!(
+        "{name:<16} {:>6} proxies {:>7} pairs {:>12.3?}/step",
+        proxies.len(),
+        pairs,
+        now.elapsed() / STEPS
+    );
+
+    pairs
+}
+
+fn main() {
+    for count in [100, 1_000, 5_000] {
+        let proxies = proxies(count);
+        let expected = bench("brute force", &mut BruteForce::new(), &proxies);
+
+        assert_eq!(
+            bench("spatial hash", &mut SpatialHash::new(2.0), &proxies),
+            expected
+        );
+        assert_eq!(
+            bench("sweep and prune", &mut SweepAndPrune::new(), &proxies),
+            expected
+        );
+        assert_eq!(
+            bench("aabb tree", &mut AabbTree::default(), &proxies),
+            expected
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use hex::nalgebra::Vector2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector2<f32>, max: Vector2<f32>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vector2<f32>>) -> Option<Self> {
+        points.into_iter().fold(None, |aabb: Option<Self>, p| {
+            Some(match aabb {
+                Some(aabb) => Self::new(aabb.min.inf(p), aabb.max.sup(p)),
+                None => Self::new(*p, *p),
+            })
+        })
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && self.max.x >= other.max.x
+            && self.max.y >= other.max.y
+    }
+
+    pub fn contains_point(&self, point: &Vector2<f32>) -> bool {
+        self.min.x <= point.x
+            && self.min.y <= point.y
+            && self.max.x >= point.x
+            && self.max.y >= point.y
+    }
+
+    pub fn merge(&self, other: &Self) -> Self {
+        Self::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    pub fn expand(&self, margin: f32) -> Self {
+        Self::new(
+            self.min - Vector2::repeat(margin),
+            self.max + Vector2::repeat(margin),
+        )
+    }
+
+    pub fn center(&self) -> Vector2<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn extents(&self) -> Vector2<f32> {
+        self.max - self.min
+    }
+
+    pub fn perimeter(&self) -> f32 {
+        let extents = self.extents();
+
+        2.0 * (extents.x + extents.y)
+    }
+}
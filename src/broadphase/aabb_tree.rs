@@ -0,0 +1,310 @@
+use super::Broadphase;
+use crate::aabb::Aabb;
+use hex::Id;
+use std::collections::{HashMap, HashSet};
+
+struct Node {
+    aabb: Aabb,
+    parent: Option<usize>,
+    children: Option<(usize, usize)>,
+    height: usize,
+    proxy: Option<(Id, Aabb)>,
+}
+
+// Leaves store their bounds fattened by `margin`, so a collider only needs to
+// be reinserted once it leaves its fat bounds.
+pub struct AabbTree {
+    pub margin: f32,
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    leaves: HashMap<Id, usize>,
+}
+
+impl AabbTree {
+    pub fn new(margin: f32) -> Self {
+        Self {
+            margin,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            leaves: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn insert(&mut self, id: Id, aabb: Aabb) {
+        self.remove(id);
+
+        let fat = aabb.expand(self.margin);
+        let leaf = self.allocate(Node {
+            aabb: fat,
+            parent: None,
+            children: None,
+            height: 0,
+            proxy: Some((id, aabb)),
+        });
+
+        self.leaves.insert(id, leaf);
+
+        let Some(mut index) = self.root else {
+            self.root = Some(leaf);
+
+            return;
+        };
+
+        while let Some((left, right)) = self.nodes[index].children {
+            let perimeter = self.nodes[index].aabb.perimeter();
+            let combined = self.nodes[index].aabb.merge(&fat).perimeter();
+            let cost = 2.0 * combined;
+            let inheritance = 2.0 * (combined - perimeter);
+            let descend = |child: usize| {
+                let node = &self.nodes[child];
+                let merged = node.aabb.merge(&fat).perimeter();
+
+                match node.children {
+                    Some(_) => merged - node.aabb.perimeter() + inheritance,
+                    None => merged + inheritance,
+                }
+            };
+            let (cost_left, cost_right) = (descend(left), descend(right));
+
+            if cost < cost_left && cost < cost_right {
+                break;
+            }
+
+            index = if cost_left < cost_right { left } else { right };
+        }
+
+        let sibling = index;
+        let grandparent = self.nodes[sibling].parent;
+        let parent = self.allocate(Node {
+            aabb: self.nodes[sibling].aabb.merge(&fat),
+            parent: grandparent,
+            children: Some((sibling, leaf)),
+            height: 0,
+            proxy: None,
+        });
+
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+
+        match grandparent {
+            Some(grandparent) => self.replace_child(grandparent, sibling, parent),
+            None => self.root = Some(parent),
+        }
+
+        self.refit(Some(parent));
+    }
+
+    pub fn remove(&mut self, id: Id) {
+        let Some(leaf) = self.leaves.remove(&id) else {
+            return;
+        };
+
+        self.free.push(leaf);
+
+        let Some(parent) = self.nodes[leaf].parent else {
+            self.root = None;
+
+            return;
+        };
+        let sibling = match self.nodes[parent].children {
+            Some((left, right)) if left == leaf => right,
+            Some((left, _)) => left,
+            None => unreachable!(),
+        };
+        let grandparent = self.nodes[parent].parent;
+
+        self.free.push(parent);
+        self.nodes[sibling].parent = grandparent;
+
+        match grandparent {
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit(Some(grandparent));
+            }
+            None => self.root = Some(sibling),
+        }
+    }
+
+    pub fn query(&self, aabb: &Aabb) -> Vec<Id> {
+        let mut ids = Vec::new();
+
+        self.visit(aabb, |leaf| {
+            if let Some((id, _)) = self.nodes[leaf].proxy {
+                ids.push(id);
+            }
+        });
+
+        ids
+    }
+
+    fn visit(&self, aabb: &Aabb, mut f: impl FnMut(usize)) {
+        let mut stack: Vec<_> = self.root.into_iter().collect();
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+
+            if !node.aabb.intersects(aabb) {
+                continue;
+            }
+
+            match (node.children, node.proxy) {
+                (Some((left, right)), _) => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                (None, Some((_, tight))) if tight.intersects(aabb) => f(index),
+                _ => {}
+            }
+        }
+    }
+
+    fn allocate(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+
+                index
+            }
+            None => {
+                self.nodes.push(node);
+
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if let Some((left, right)) = &mut self.nodes[parent].children {
+            if *left == old {
+                *left = new;
+            } else if *right == old {
+                *right = new;
+            }
+        }
+    }
+
+    fn refit(&mut self, mut index: Option<usize>) {
+        while let Some(i) = index {
+            let i = self.balance(i);
+
+            if let Some((left, right)) = self.nodes[i].children {
+                self.nodes[i].height = 1 + self.nodes[left].height.max(self.nodes[right].height);
+                self.nodes[i].aabb = self.nodes[left].aabb.merge(&self.nodes[right].aabb);
+            }
+
+            index = self.nodes[i].parent;
+        }
+    }
+
+    fn balance(&mut self, index: usize) -> usize {
+        let Some((left, right)) = self.nodes[index].children else {
+            return index;
+        };
+        let balance = self.nodes[right].height as isize - self.nodes[left].height as isize;
+
+        if balance > 1 {
+            self.rotate(index, right, left)
+        } else if balance < -1 {
+            self.rotate(index, left, right)
+        } else {
+            index
+        }
+    }
+
+    // Lifts `up` into the place of its parent `index`, which keeps `other` and
+    // the shorter of `up`'s children.
+    fn rotate(&mut self, index: usize, up: usize, other: usize) -> usize {
+        let Some((first, second)) = self.nodes[up].children else {
+            return index;
+        };
+        let (keep, give) = if self.nodes[first].height > self.nodes[second].height {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let parent = self.nodes[index].parent;
+
+        self.nodes[up].parent = parent;
+        self.nodes[index].parent = Some(up);
+
+        match parent {
+            Some(parent) => self.replace_child(parent, index, up),
+            None => self.root = Some(up),
+        }
+
+        self.nodes[give].parent = Some(index);
+        self.nodes[index].children = Some((other, give));
+        self.nodes[index].height = 1 + self.nodes[other].height.max(self.nodes[give].height);
+        self.nodes[index].aabb = self.nodes[other].aabb.merge(&self.nodes[give].aabb);
+        self.nodes[up].children = Some((index, keep));
+        self.nodes[up].height = 1 + self.nodes[index].height.max(self.nodes[keep].height);
+        self.nodes[up].aabb = self.nodes[index].aabb.merge(&self.nodes[keep].aabb);
+
+        up
+    }
+}
+
+impl Default for AabbTree {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl Broadphase for AabbTree {
+    fn update(&mut self, proxies: &[(Id, Aabb)]) {
+        let ids: HashSet<_> = proxies.iter().map(|(id, _)| *id).collect();
+        let stale: Vec<_> = self
+            .leaves
+            .keys()
+            .filter(|id| !ids.contains(id))
+            .cloned()
+            .collect();
+
+        for id in stale {
+            self.remove(id);
+        }
+
+        for (id, aabb) in proxies {
+            match self.leaves.get(id) {
+                Some(&leaf) if self.nodes[leaf].aabb.contains(aabb) => {
+                    self.nodes[leaf].proxy = Some((*id, *aabb));
+                }
+                _ => self.insert(*id, *aabb),
+            }
+        }
+    }
+
+    fn pairs(&self) -> Vec<(Id, Id)> {
+        let mut pairs = Vec::new();
+
+        for (id, leaf) in &self.leaves {
+            let Some((_, aabb)) = self.nodes[*leaf].proxy else {
+                continue;
+            };
+
+            self.visit(&aabb, |other| {
+                if other > *leaf {
+                    if let Some((id2, _)) = self.nodes[other].proxy {
+                        pairs.push((*id, id2));
+                    }
+                }
+            });
+        }
+
+        pairs
+    }
+
+    fn query(&self, aabb: &Aabb) -> Vec<Id> {
+        AabbTree::query(self, aabb)
+    }
+}
@@ -0,0 +1,43 @@
+use super::Broadphase;
+use crate::aabb::Aabb;
+use hex::Id;
+
+#[derive(Default)]
+pub struct BruteForce {
+    proxies: Vec<(Id, Aabb)>,
+}
+
+impl BruteForce {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Broadphase for BruteForce {
+    fn update(&mut self, proxies: &[(Id, Aabb)]) {
+        self.proxies.clear();
+        self.proxies.extend_from_slice(proxies);
+    }
+
+    fn pairs(&self) -> Vec<(Id, Id)> {
+        let mut pairs = Vec::new();
+
+        for (i, (e, aabb)) in self.proxies.iter().enumerate() {
+            for (e2, aabb2) in &self.proxies[i + 1..] {
+                if aabb.intersects(aabb2) {
+                    pairs.push((*e, *e2));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    fn query(&self, aabb: &Aabb) -> Vec<Id> {
+        self.proxies
+            .iter()
+            .filter(|(_, aabb2)| aabb.intersects(aabb2))
+            .map(|(e, _)| *e)
+            .collect()
+    }
+}
@@ -0,0 +1,24 @@
+pub mod aabb_tree;
+pub mod brute_force;
+pub mod spatial_hash;
+pub mod sweep_and_prune;
+
+pub use aabb_tree::AabbTree;
+pub use brute_force::BruteForce;
+pub use spatial_hash::SpatialHash;
+pub use sweep_and_prune::SweepAndPrune;
+
+use crate::aabb::Aabb;
+use hex::Id;
+
+pub trait Broadphase: Send + Sync {
+    // Receives every collider's world-space bounds once per step, and again
+    // before the first query after a step.
+    fn update(&mut self, proxies: &[(Id, Aabb)]);
+
+    // Returns each pair of proxies whose bounds overlap, once.
+    fn pairs(&self) -> Vec<(Id, Id)>;
+
+    // Returns every proxy whose bounds overlap `aabb`.
+    fn query(&self, aabb: &Aabb) -> Vec<Id>;
+}
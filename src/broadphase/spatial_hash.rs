@@ -0,0 +1,119 @@
+use super::Broadphase;
+use crate::aabb::Aabb;
+use hex::Id;
+use std::collections::{HashMap, HashSet};
+
+pub struct SpatialHash {
+    pub cell_size: f32,
+    pub max_cells: usize,
+    proxies: Vec<(Id, Aabb)>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    oversized: Vec<usize>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            max_cells: 64,
+            proxies: Vec::new(),
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+        }
+    }
+
+    fn cell(&self, value: f32) -> i32 {
+        (value / self.cell_size).floor() as i32
+    }
+}
+
+impl Broadphase for SpatialHash {
+    fn update(&mut self, proxies: &[(Id, Aabb)]) {
+        self.proxies.clear();
+        self.proxies.extend_from_slice(proxies);
+        self.oversized.clear();
+
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+
+        for (i, (_, aabb)) in proxies.iter().enumerate() {
+            let cells = (aabb.extents() / self.cell_size).map(|c| c.ceil() + 1.0);
+
+            // Anything spanning too many cells would flood the map, so it is
+            // tested against every other proxy instead.
+            if cells.x * cells.y > self.max_cells as f32 {
+                self.oversized.push(i);
+
+                continue;
+            }
+
+            let (x1, y1) = (self.cell(aabb.min.x), self.cell(aabb.min.y));
+            let (x2, y2) = (self.cell(aabb.max.x), self.cell(aabb.max.y));
+
+            for x in x1..=x2 {
+                for y in y1..=y2 {
+                    self.cells.entry((x, y)).or_default().push(i);
+                }
+            }
+        }
+
+        self.cells.retain(|_, cell| !cell.is_empty());
+    }
+
+    fn pairs(&self) -> Vec<(Id, Id)> {
+        let mut pairs = HashSet::new();
+        let mut test = |i: usize, j: usize| {
+            let (i, j) = (i.min(j), i.max(j));
+
+            if i != j && self.proxies[i].1.intersects(&self.proxies[j].1) {
+                pairs.insert((i, j));
+            }
+        };
+
+        for cell in self.cells.values() {
+            for (k, i) in cell.iter().enumerate() {
+                for j in &cell[k + 1..] {
+                    test(*i, *j);
+                }
+            }
+        }
+
+        for i in &self.oversized {
+            for j in 0..self.proxies.len() {
+                test(*i, j);
+            }
+        }
+
+        pairs
+            .into_iter()
+            .map(|(i, j)| (self.proxies[i].0, self.proxies[j].0))
+            .collect()
+    }
+
+    fn query(&self, aabb: &Aabb) -> Vec<Id> {
+        let cells = (aabb.extents() / self.cell_size).map(|c| c.ceil() + 1.0);
+        let mut found: HashSet<_> = self.oversized.iter().cloned().collect();
+
+        if cells.x * cells.y > self.max_cells as f32 {
+            found.extend(0..self.proxies.len());
+        } else {
+            let (x1, y1) = (self.cell(aabb.min.x), self.cell(aabb.min.y));
+            let (x2, y2) = (self.cell(aabb.max.x), self.cell(aabb.max.y));
+
+            for x in x1..=x2 {
+                for y in y1..=y2 {
+                    if let Some(cell) = self.cells.get(&(x, y)) {
+                        found.extend(cell);
+                    }
+                }
+            }
+        }
+
+        found
+            .into_iter()
+            .filter(|i| self.proxies[*i].1.intersects(aabb))
+            .map(|i| self.proxies[i].0)
+            .collect()
+    }
+}
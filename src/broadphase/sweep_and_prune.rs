@@ -0,0 +1,53 @@
+use super::Broadphase;
+use crate::aabb::Aabb;
+use hex::Id;
+
+#[derive(Default)]
+pub struct SweepAndPrune {
+    proxies: Vec<(Id, Aabb)>,
+}
+
+impl SweepAndPrune {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Broadphase for SweepAndPrune {
+    fn update(&mut self, proxies: &[(Id, Aabb)]) {
+        self.proxies.clear();
+        self.proxies.extend_from_slice(proxies);
+        self.proxies
+            .sort_unstable_by(|(_, a), (_, b)| a.min.x.total_cmp(&b.min.x));
+    }
+
+    fn pairs(&self) -> Vec<(Id, Id)> {
+        let mut pairs = Vec::new();
+        let mut active: Vec<usize> = Vec::new();
+
+        for (i, (e, aabb)) in self.proxies.iter().enumerate() {
+            active.retain(|j| self.proxies[*j].1.max.x >= aabb.min.x);
+
+            for j in &active {
+                let (e2, aabb2) = &self.proxies[*j];
+
+                if aabb.min.y <= aabb2.max.y && aabb.max.y >= aabb2.min.y {
+                    pairs.push((*e2, *e));
+                }
+            }
+
+            active.push(i);
+        }
+
+        pairs
+    }
+
+    fn query(&self, aabb: &Aabb) -> Vec<Id> {
+        self.proxies
+            .iter()
+            .take_while(|(_, aabb2)| aabb2.min.x <= aabb.max.x)
+            .filter(|(_, aabb2)| aabb.intersects(aabb2))
+            .map(|(e, _)| *e)
+            .collect()
+    }
+}
@@ -1,39 +1,117 @@
+use crate::{
+    aabb::Aabb,
+    contact::{ClosestPoints, ContactManifold, Narrowphase},
+    gjk,
+    narrowphase::{self, Convex},
+    shape::{Child, Shape, ShapeError},
+    tilemap::Tilemap,
+};
 use hex::{
     components::Trans,
-    nalgebra::{Vector2, Vector3},
+    nalgebra::{Matrix3, Vector2, Vector3},
     parking_lot::RwLock,
     Id,
 };
 use std::sync::Arc;
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BodyType {
+    Static,
+    Kinematic,
+    #[default]
+    Dynamic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl Material {
+    pub fn new(restitution: f32, friction: f32) -> Self {
+        Self {
+            restitution,
+            friction,
+        }
+    }
+
+    pub fn combine(&self, other: &Self) -> Self {
+        Self::new(
+            self.restitution.max(other.restitution),
+            (self.friction * other.friction).sqrt(),
+        )
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(0.0, 0.5)
+    }
+}
+
+// Bitmask filtering. Two filters pass when each one's category is in the
+// other's mask, unless both share the same nonzero group, which never collides.
+//
+// Filters only add to the layer rule: pieces that pass must still share a
+// layer, and neither collider may ignore a layer of the other, so colliders
+// without layers never collide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionFilter {
+    pub category: u32,
+    pub mask: u32,
+    pub group: i32,
+}
+
+impl CollisionFilter {
+    pub fn new(category: u32, mask: u32, group: i32) -> Self {
+        Self {
+            category,
+            mask,
+            group,
+        }
+    }
+
+    pub fn passes(&self, other: &Self) -> bool {
+        !(self.group != 0 && self.group == other.group)
+            && self.category & other.mask != 0
+            && other.category & self.mask != 0
+    }
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::new(1, u32::MAX, 0)
+    }
+}
+
 #[derive(Clone)]
 pub struct Collider {
-    pub points: Vec<Vector2<f32>>,
-    pub boundary: f32,
+    pub shape: Shape,
     pub layers: Vec<Id>,
     pub ignore: Vec<Id>,
-    pub collisions: Vec<Id>,
+    pub body_type: BodyType,
+    pub material: Material,
+    pub filter: CollisionFilter,
     pub ghost: bool,
-    pub log_collisions: bool,
 }
 
 impl Collider {
     pub fn new(
-        points: Vec<Vector2<f32>>,
-        boundary: f32,
+        shape: Shape,
         layers: Vec<Id>,
         ignore: Vec<Id>,
+        body_type: BodyType,
         ghost: bool,
-        log_collisions: bool,
     ) -> Arc<RwLock<Self>> {
         Arc::new(RwLock::new(Self {
-            points,
-            boundary,
+            shape,
             layers,
             ignore,
-            collisions: Vec::new(),
+            body_type,
+            material: Material::default(),
+            filter: CollisionFilter::default(),
             ghost,
-            log_collisions,
         }))
     }
 
@@ -41,99 +119,464 @@ impl Collider {
         dims: Vector2<f32>,
         layer: Vec<Id>,
         ignore: Vec<Id>,
+        body_type: BodyType,
         ghost: bool,
-        log_collisions: bool,
     ) -> Arc<RwLock<Self>> {
         let dims1 = dims / 2.0;
 
         Self::new(
-            vec![
+            Shape::Polygon(vec![
                 Vector2::new(-dims1.x, -dims1.y),
                 Vector2::new(-dims1.x, dims1.y),
                 Vector2::new(dims1.x, dims1.y),
                 Vector2::new(dims1.x, -dims1.y),
-            ],
-            dims.magnitude(),
+            ]),
             layer,
             ignore,
+            body_type,
             ghost,
-            log_collisions,
         )
     }
 
+    pub fn circle(
+        radius: f32,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(Shape::circle(radius), layers, ignore, body_type, ghost)
+    }
+
+    pub fn capsule(
+        height: f32,
+        radius: f32,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(
+            Shape::capsule(height, radius),
+            layers,
+            ignore,
+            body_type,
+            ghost,
+        )
+    }
+
+    pub fn edge(
+        a: Vector2<f32>,
+        b: Vector2<f32>,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(Shape::edge(a, b), layers, ignore, body_type, ghost)
+    }
+
+    pub fn chain(
+        points: Vec<Vector2<f32>>,
+        closed: bool,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(
+            Shape::chain(points, closed),
+            layers,
+            ignore,
+            body_type,
+            ghost,
+        )
+    }
+
+    pub fn compound(
+        children: Vec<Child>,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(Shape::Compound(children), layers, ignore, body_type, ghost)
+    }
+
+    pub fn convex_hull(
+        points: &[Vector2<f32>],
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Result<Arc<RwLock<Self>>, ShapeError> {
+        Ok(Self::new(
+            Shape::convex_hull(points)?,
+            layers,
+            ignore,
+            body_type,
+            ghost,
+        ))
+    }
+
+    pub fn concave(
+        points: Vec<Vector2<f32>>,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Result<Arc<RwLock<Self>>, ShapeError> {
+        Ok(Self::new(
+            Shape::concave(points)?,
+            layers,
+            ignore,
+            body_type,
+            ghost,
+        ))
+    }
+
+    pub fn tilemap(
+        tilemap: Tilemap,
+        layers: Vec<Id>,
+        ignore: Vec<Id>,
+        body_type: BodyType,
+        ghost: bool,
+    ) -> Arc<RwLock<Self>> {
+        Self::new(Shape::Tilemap(tilemap), layers, ignore, body_type, ghost)
+    }
+
+    pub fn tilemap_mut(&mut self) -> Option<&mut Tilemap> {
+        match &mut self.shape {
+            Shape::Tilemap(tilemap) => Some(tilemap),
+            _ => None,
+        }
+    }
+
+    pub fn child(&self, child: usize) -> Option<&Child> {
+        match &self.shape {
+            Shape::Compound(children) => children.get(child),
+            _ => None,
+        }
+    }
+
+    pub fn layers_of(&self, child: usize) -> &[Id] {
+        self.child(child)
+            .and_then(|c| c.layers.as_deref())
+            .unwrap_or(&self.layers)
+    }
+
+    pub fn material_of(&self, child: usize) -> Material {
+        self.child(child)
+            .and_then(|c| c.material)
+            .unwrap_or(self.material)
+    }
+
+    pub fn filter_of(&self, child: usize) -> CollisionFilter {
+        self.child(child)
+            .and_then(|c| c.filter)
+            .unwrap_or(self.filter)
+    }
+
+    // Two pieces interact when their filters pass each other, they share a
+    // layer and neither collider ignores a layer of the other piece. Every
+    // check is symmetric, so the order of the pieces never matters.
+    pub fn interacts(&self, child: usize, c2: &Self, child2: usize) -> bool {
+        if !self.filter_of(child).passes(&c2.filter_of(child2)) {
+            return false;
+        }
+
+        let layers = self.layers_of(child);
+        let layers2 = c2.layers_of(child2);
+
+        layers.iter().any(|a| layers2.contains(a))
+            && !(self.ignore.iter().any(|a| layers2.contains(a))
+                || c2.ignore.iter().any(|b| layers.contains(b)))
+    }
+
     pub fn intersecting(
         &self,
         transform: &Trans,
         transform2: &Trans,
         c2: &Self,
     ) -> Option<Vector2<f32>> {
-        let points: Vec<_> = self
-            .points
-            .iter()
-            .cloned()
-            .map(|p| (transform.matrix() * Vector3::new(p.x, p.y, 1.0)).xy())
+        self.intersecting_at(&transform.matrix(), &transform2.matrix(), c2)
+    }
+
+    pub fn intersecting_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+    ) -> Option<Vector2<f32>> {
+        self.contact_at(matrix, matrix2, c2).map(|m| m.mtv())
+    }
+
+    pub fn contact(
+        &self,
+        transform: &Trans,
+        transform2: &Trans,
+        c2: &Self,
+    ) -> Option<ContactManifold> {
+        self.contact_at(&transform.matrix(), &transform2.matrix(), c2)
+    }
+
+    // The deepest of the manifolds between the two colliders' pieces.
+    pub fn contact_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+    ) -> Option<ContactManifold> {
+        self.contacts_at(matrix, matrix2, c2)
+            .into_iter()
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+
+    pub fn contacts(
+        &self,
+        transform: &Trans,
+        transform2: &Trans,
+        c2: &Self,
+    ) -> Vec<ContactManifold> {
+        self.contacts_at(&transform.matrix(), &transform2.matrix(), c2)
+    }
+
+    pub fn contacts_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+    ) -> Vec<ContactManifold> {
+        self.contacts_with(matrix, matrix2, c2, Narrowphase::Sat)
+    }
+
+    pub fn contacts_with(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+        narrowphase: Narrowphase,
+    ) -> Vec<ContactManifold> {
+        self.contacts_filtered(matrix, matrix2, c2, narrowphase, true, |_, _| true)
+    }
+
+    // With `one_sided` unset, edges and chains collide from both sides, e.g.
+    // for overlap queries that only ask whether the areas intersect.
+    pub(crate) fn contacts_filtered(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+        narrowphase: Narrowphase,
+        one_sided: bool,
+        filter: impl Fn(usize, usize) -> bool,
+    ) -> Vec<ContactManifold> {
+        let collide = match narrowphase {
+            Narrowphase::Sat => narrowphase::convex,
+            Narrowphase::Gjk => gjk::convex,
+        };
+        let bounded = |(child, mut convex): (usize, Convex)| {
+            if !one_sided {
+                convex.adjacent = None;
+            }
+
+            Some((child, convex.aabb()?, convex))
+        };
+        let leaves2: Vec<_> = c2
+            .shape
+            .leaves_within(matrix2, self.aabb_at(matrix).as_ref())
+            .into_iter()
+            .filter_map(bounded)
             .collect();
-        let points2: Vec<_> = c2
-            .points
+        let Some(aabb2) = leaves2
             .iter()
-            .cloned()
-            .map(|p| (transform2.matrix() * Vector3::new(p.x, p.y, 1.0)).xy())
-            .collect();
+            .map(|(_, aabb, _)| *aabb)
+            .reduce(|a, b| a.merge(&b))
+        else {
+            return Vec::new();
+        };
 
-        let mut min = None;
+        self.shape
+            .leaves_within(matrix, Some(&aabb2))
+            .into_iter()
+            .filter_map(bounded)
+            .flat_map(|(child, aabb, convex)| {
+                leaves2
+                    .iter()
+                    .filter(|(child2, aabb2, _)| aabb.intersects(aabb2) && filter(child, *child2))
+                    .filter_map(|(child2, _, convex2)| {
+                        Some(ContactManifold {
+                            child,
+                            other_child: *child2,
+                            ..collide(&convex, convex2)?
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 
-        for i in 0..points.len() {
-            let p1 = points[i];
-            let p2 = points[(i + 1) % points.len()];
-            let axis = Vector2::new(p2.y - p1.y, p1.x - p2.x).normalize();
+    pub fn distance(
+        &self,
+        transform: &Trans,
+        transform2: &Trans,
+        c2: &Self,
+    ) -> Option<ClosestPoints> {
+        self.distance_at(&transform.matrix(), &transform2.matrix(), c2)
+    }
 
-            let mut a_min = None;
-            let mut a_max = None;
+    // The closest pair of pieces by GJK, or the deepest by EPA if any overlap.
+    pub fn distance_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+    ) -> Option<ClosestPoints> {
+        let leaves2 = c2.shape.leaves(matrix2);
 
-            for p in &points {
-                let projected = axis.dot(p);
+        self.shape
+            .leaves(matrix)
+            .iter()
+            .flat_map(|(child, convex)| {
+                leaves2.iter().filter_map(|(child2, convex2)| {
+                    Some(ClosestPoints {
+                        child: *child,
+                        other_child: *child2,
+                        ..gjk::distance(convex, convex2)?
+                    })
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
 
-                if a_min.map(|a| projected < a).unwrap_or(true) {
-                    a_min = Some(projected);
-                }
+    pub fn contains_at(&self, matrix: &Matrix3<f32>, point: Vector2<f32>) -> bool {
+        self.contains_filtered(matrix, point, |_| true)
+    }
 
-                if a_max.map(|a| projected > a).unwrap_or(true) {
-                    a_max = Some(projected);
-                }
-            }
+    pub(crate) fn contains_filtered(
+        &self,
+        matrix: &Matrix3<f32>,
+        point: Vector2<f32>,
+        filter: impl Fn(usize) -> bool,
+    ) -> bool {
+        self.shape
+            .leaves_within(matrix, Some(&Aabb::new(point, point)))
+            .iter()
+            .any(|(child, convex)| filter(*child) && narrowphase::contains(convex, point))
+    }
+
+    // The closest piece hit by the ray, its distance along the unit `direction`
+    // and the surface normal there.
+    pub fn raycast_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Option<(usize, f32, Vector2<f32>)> {
+        self.raycast_filtered(matrix, origin, direction, max_distance, |_| true)
+    }
 
-            let mut b_min = None;
-            let mut b_max = None;
+    pub(crate) fn raycast_filtered(
+        &self,
+        matrix: &Matrix3<f32>,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+        filter: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f32, Vector2<f32>)> {
+        let end = origin + direction * max_distance;
+        let bounds = Aabb::from_points(&[origin, end]).filter(|_| max_distance.is_finite());
 
-            for p in &points2 {
-                let projected = axis.dot(p);
+        self.shape
+            .leaves_within(matrix, bounds.as_ref())
+            .into_iter()
+            .filter(|(child, _)| filter(*child))
+            .filter_map(|(child, convex)| {
+                let (t, normal) = narrowphase::raycast(&convex, origin, direction, max_distance)?;
 
-                if b_min.map(|b| projected < b).unwrap_or(true) {
-                    b_min = Some(projected);
-                }
+                Some((child, t, normal))
+            })
+            .min_by(|(_, t, _), (_, t2, _)| t.total_cmp(t2))
+    }
 
-                if b_max.map(|b| projected > b).unwrap_or(true) {
-                    b_max = Some(projected);
-                }
-            }
+    // Sweeps this collider by `translation` against a resting `c2`. Returns the
+    // fraction of the translation at first contact and a manifold whose normal
+    // points from this collider toward `c2`.
+    pub fn cast_at(
+        &self,
+        matrix: &Matrix3<f32>,
+        translation: Vector2<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+    ) -> Option<(f32, ContactManifold)> {
+        self.cast_filtered(matrix, translation, matrix2, c2, |_, _| true)
+    }
 
-            let a_max = a_max?;
-            let a_min = a_min?;
-            let b_max = b_max?;
-            let b_min = b_min?;
-            let (m, axis) = if a_min <= b_min && a_max >= b_min {
-                Some((a_max - b_min, axis))
-            } else if b_min <= a_min && b_max >= a_min {
-                Some((b_max - a_min, -axis))
-            } else {
-                None
-            }?;
-
-            if min.map(|(min, _)| m < min).unwrap_or(true) {
-                min = Some((m, axis));
-            }
-        }
+    pub(crate) fn cast_filtered(
+        &self,
+        matrix: &Matrix3<f32>,
+        translation: Vector2<f32>,
+        matrix2: &Matrix3<f32>,
+        c2: &Self,
+        filter: impl Fn(usize, usize) -> bool,
+    ) -> Option<(f32, ContactManifold)> {
+        let swept = self
+            .aabb_at(matrix)
+            .map(|aabb| aabb.merge(&Aabb::new(aabb.min + translation, aabb.max + translation)));
+        let leaves2 = c2.shape.leaves_within(matrix2, swept.as_ref());
+
+        self.shape
+            .leaves(matrix)
+            .iter()
+            .flat_map(|(child, convex)| {
+                leaves2
+                    .iter()
+                    .filter(|(child2, _)| filter(*child, *child2))
+                    .filter_map(|(child2, convex2)| {
+                        let (toi, manifold) = narrowphase::cast(convex, convex2, translation)?;
+
+                        Some((
+                            toi,
+                            ContactManifold {
+                                child: *child,
+                                other_child: *child2,
+                                ..manifold
+                            },
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .min_by(|(toi, _), (toi2, _)| toi.total_cmp(toi2))
+    }
+
+    // The radius of a circle around the collider's origin enclosing the shape.
+    pub fn boundary(&self) -> f32 {
+        self.shape.radius()
+    }
+
+    pub fn boundary_at(&self, matrix: &Matrix3<f32>) -> f32 {
+        let origin = (matrix * Vector3::new(0.0, 0.0, 1.0)).xy();
+
+        self.shape
+            .leaves(matrix)
+            .iter()
+            .map(|(_, convex)| {
+                convex
+                    .points
+                    .iter()
+                    .map(|p| (p - origin).magnitude())
+                    .fold(0.0, f32::max)
+                    + convex.radius
+            })
+            .fold(0.0, f32::max)
+    }
+
+    pub fn aabb(&self, transform: &Trans) -> Option<Aabb> {
+        self.aabb_at(&transform.matrix())
+    }
 
-        min.map(|(m, a)| a.normalize() * m)
+    pub fn aabb_at(&self, matrix: &Matrix3<f32>) -> Option<Aabb> {
+        self.shape.aabb(matrix)
     }
 }
@@ -1,3 +1,7 @@
 pub mod collider;
+pub mod parent;
+pub mod rigid_body;
 
-pub use collider::Collider;
+pub use collider::{BodyType, Collider, CollisionFilter, Material};
+pub use parent::Parent;
+pub use rigid_body::RigidBody;
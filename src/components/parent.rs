@@ -0,0 +1,17 @@
+use hex::{parking_lot::RwLock, Id};
+use std::sync::Arc;
+
+// Attaches an entity to another: its `Trans` is local to the parent's. Parented
+// colliders follow their parent and are never moved by the solver. Parented
+// entities are not simulated either: a `RigidBody` on one is ignored, so its
+// velocity, forces and gravity have no effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parent {
+    pub entity: Id,
+}
+
+impl Parent {
+    pub fn new(entity: Id) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self { entity }))
+    }
+}
@@ -0,0 +1,118 @@
+use super::BodyType;
+use hex::{components::Trans, nalgebra::Vector2, parking_lot::RwLock};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct RigidBody {
+    pub velocity: Vector2<f32>,
+    pub angular_velocity: f32,
+    pub mass: f32,
+    pub inertia: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub force: Vector2<f32>,
+    pub torque: f32,
+    pub previous: Option<(Vector2<f32>, f32)>,
+}
+
+impl RigidBody {
+    pub fn new(
+        mass: f32,
+        inertia: f32,
+        linear_damping: f32,
+        angular_damping: f32,
+    ) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            velocity: Vector2::zeros(),
+            angular_velocity: 0.0,
+            mass,
+            inertia,
+            linear_damping,
+            angular_damping,
+            force: Vector2::zeros(),
+            torque: 0.0,
+            previous: None,
+        }))
+    }
+
+    // A non-positive or infinite mass (or inertia) makes the body immovable.
+    pub fn inverse_mass(&self) -> f32 {
+        Self::inverse(self.mass)
+    }
+
+    pub fn inverse_inertia(&self) -> f32 {
+        Self::inverse(self.inertia)
+    }
+
+    pub fn apply_force(&mut self, force: Vector2<f32>) {
+        self.force += force;
+    }
+
+    pub fn apply_force_at(&mut self, force: Vector2<f32>, offset: Vector2<f32>) {
+        self.force += force;
+        self.torque += offset.perp(&force);
+    }
+
+    pub fn apply_torque(&mut self, torque: f32) {
+        self.torque += torque;
+    }
+
+    pub fn apply_impulse(&mut self, impulse: Vector2<f32>) {
+        self.velocity += impulse * self.inverse_mass();
+    }
+
+    pub fn apply_impulse_at(&mut self, impulse: Vector2<f32>, offset: Vector2<f32>) {
+        self.velocity += impulse * self.inverse_mass();
+        self.angular_velocity += offset.perp(&impulse) * self.inverse_inertia();
+    }
+
+    // Static bodies never move. Kinematic bodies follow their velocity but
+    // ignore forces, torque, damping and gravity.
+    pub fn integrate_velocity(&mut self, body_type: BodyType, gravity: Vector2<f32>, delta: f32) {
+        if body_type == BodyType::Dynamic {
+            let inverse_mass = self.inverse_mass();
+
+            if inverse_mass > 0.0 {
+                self.velocity += (self.force * inverse_mass + gravity) * delta;
+            }
+
+            self.angular_velocity += self.torque * self.inverse_inertia() * delta;
+            self.velocity *= 1.0 / (1.0 + delta * self.linear_damping);
+            self.angular_velocity *= 1.0 / (1.0 + delta * self.angular_damping);
+        }
+
+        self.force = Vector2::zeros();
+        self.torque = 0.0;
+    }
+
+    pub fn integrate_position(&mut self, transform: &mut Trans, body_type: BodyType, delta: f32) {
+        if body_type == BodyType::Static {
+            return;
+        }
+
+        self.previous = Some((transform.position(), transform.rotation()));
+
+        transform.set_position(transform.position() + self.velocity * delta);
+        transform.set_rotation(transform.rotation() + self.angular_velocity * delta);
+    }
+
+    // Blends the pose before the last step with the current one, `alpha` being
+    // the fraction of a step left in the `PhysicsManager` accumulator.
+    pub fn interpolated(&self, transform: &Trans, alpha: f32) -> (Vector2<f32>, f32) {
+        match self.previous {
+            Some((position, rotation)) => (
+                position.lerp(&transform.position(), alpha),
+                rotation + (transform.rotation() - rotation) * alpha,
+            ),
+            None => (transform.position(), transform.rotation()),
+        }
+    }
+
+    fn inverse(value: f32) -> f32 {
+        if value > 0.0 && value.is_finite() {
+            1.0 / value
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use hex::nalgebra::Vector2;
+
+// Which algorithm finds contacts between convex pieces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Narrowphase {
+    #[default]
+    Sat,
+    Gjk,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactManifold {
+    pub normal: Vector2<f32>,
+    pub depth: f32,
+    pub points: Vec<Vector2<f32>>,
+    pub child: usize,
+    pub other_child: usize,
+}
+
+impl ContactManifold {
+    pub fn new(normal: Vector2<f32>, depth: f32, points: Vec<Vector2<f32>>) -> Self {
+        Self {
+            normal,
+            depth,
+            points,
+            child: 0,
+            other_child: 0,
+        }
+    }
+
+    pub fn mtv(&self) -> Vector2<f32> {
+        self.normal * self.depth
+    }
+}
+
+// `distance` is negative when the shapes overlap, in which case it is the
+// penetration depth. `normal` points from `point` on the first shape toward the
+// second shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoints {
+    pub distance: f32,
+    pub normal: Vector2<f32>,
+    pub point: Vector2<f32>,
+    pub other_point: Vector2<f32>,
+    pub child: usize,
+    pub other_child: usize,
+}
@@ -0,0 +1,198 @@
+use hex::nalgebra::Vector2;
+
+const EPSILON: f32 = 1e-6;
+
+// Splits a simple counter-clockwise polygon into convex pieces: ear clipping
+// followed by Hertel-Mehlhorn removal of unneeded diagonals.
+pub fn decompose(points: &[Vector2<f32>]) -> Vec<Vec<Vector2<f32>>> {
+    let points = simplify(points);
+    let mut pieces = triangulate(&points);
+    let mut merged = true;
+
+    while merged {
+        merged = false;
+
+        'search: for i in 0..pieces.len() {
+            for j in (i + 1)..pieces.len() {
+                if let Some(piece) = merge(&pieces[i], &pieces[j], &points) {
+                    pieces[i] = piece;
+                    pieces.swap_remove(j);
+                    merged = true;
+
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    pieces
+        .into_iter()
+        .map(|piece| simplify(&piece.iter().map(|i| points[*i]).collect::<Vec<_>>()))
+        .collect()
+}
+
+// Andrew's monotone chain; the hull comes out counter-clockwise without
+// collinear points.
+pub fn hull(points: &[Vector2<f32>]) -> Vec<Vector2<f32>> {
+    let mut points = points.to_vec();
+
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<Vector2<f32>> = Vec::new();
+    let push = |hull: &mut Vec<_>, p: Vector2<f32>, start: usize| {
+        while hull.len() >= start + 2
+            && turn(hull[hull.len() - 2], hull[hull.len() - 1], p) <= EPSILON
+        {
+            hull.pop();
+        }
+
+        hull.push(p);
+    };
+
+    for p in &points {
+        push(&mut hull, *p, 0);
+    }
+
+    let start = hull.len() - 1;
+
+    for p in points.iter().rev().skip(1) {
+        push(&mut hull, *p, start);
+    }
+
+    hull.pop();
+    hull
+}
+
+pub fn turn(a: Vector2<f32>, b: Vector2<f32>, c: Vector2<f32>) -> f32 {
+    (b - a).perp(&(c - b))
+}
+
+pub fn segments_intersect(
+    a: Vector2<f32>,
+    b: Vector2<f32>,
+    c: Vector2<f32>,
+    d: Vector2<f32>,
+) -> bool {
+    let (d1, d2) = (turn(a, b, c), turn(a, b, d));
+    let (d3, d4) = (turn(c, d, a), turn(c, d, b));
+    let on = |p: Vector2<f32>, q: Vector2<f32>, r: Vector2<f32>| {
+        r.x >= p.x.min(q.x) - EPSILON
+            && r.x <= p.x.max(q.x) + EPSILON
+            && r.y >= p.y.min(q.y) - EPSILON
+            && r.y <= p.y.max(q.y) + EPSILON
+    };
+
+    (d1 * d2 < 0.0 && d3 * d4 < 0.0)
+        || (d1.abs() <= EPSILON && on(a, b, c))
+        || (d2.abs() <= EPSILON && on(a, b, d))
+        || (d3.abs() <= EPSILON && on(c, d, a))
+        || (d4.abs() <= EPSILON && on(c, d, b))
+}
+
+// Drops collinear vertices so every remaining corner is a real turn.
+fn simplify(points: &[Vector2<f32>]) -> Vec<Vector2<f32>> {
+    let mut points = points.to_vec();
+    let mut i = 0;
+
+    while points.len() > 3 && i < points.len() {
+        let len = points.len();
+
+        if turn(
+            points[(i + len - 1) % len],
+            points[i],
+            points[(i + 1) % len],
+        )
+        .abs()
+            <= EPSILON
+        {
+            points.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+
+    points
+}
+
+fn triangulate(points: &[Vector2<f32>]) -> Vec<Vec<usize>> {
+    let mut remaining: Vec<_> = (0..points.len()).collect();
+    let mut triangles = Vec::new();
+
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let ear = (0..len)
+            .find(|i| {
+                let (a, b, c) = (
+                    remaining[(i + len - 1) % len],
+                    remaining[*i],
+                    remaining[(i + 1) % len],
+                );
+
+                turn(points[a], points[b], points[c]) > EPSILON
+                    && remaining
+                        .iter()
+                        .filter(|p| ![a, b, c].contains(p))
+                        .all(|p| !inside(points[*p], points[a], points[b], points[c]))
+            })
+            .or_else(|| {
+                (0..len).find(|i| {
+                    turn(
+                        points[remaining[(i + len - 1) % len]],
+                        points[remaining[*i]],
+                        points[remaining[(i + 1) % len]],
+                    ) > 0.0
+                })
+            })
+            .unwrap_or(0);
+
+        triangles.push(vec![
+            remaining[(ear + len - 1) % len],
+            remaining[ear],
+            remaining[(ear + 1) % len],
+        ]);
+        remaining.remove(ear);
+    }
+
+    triangles.push(remaining);
+    triangles
+}
+
+fn inside(p: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>, c: Vector2<f32>) -> bool {
+    turn(a, b, p) >= -EPSILON && turn(b, c, p) >= -EPSILON && turn(c, a, p) >= -EPSILON
+}
+
+// Joins two pieces across a shared diagonal if the result stays convex.
+fn merge(p: &[usize], q: &[usize], points: &[Vector2<f32>]) -> Option<Vec<usize>> {
+    let (i, j) = (0..p.len()).find_map(|i| {
+        let (a, b) = (p[i], p[(i + 1) % p.len()]);
+
+        (0..q.len())
+            .find(|j| q[*j] == b && q[(j + 1) % q.len()] == a)
+            .map(|j| (i, j))
+    })?;
+    let merged: Vec<_> = p
+        .iter()
+        .cycle()
+        .skip(i + 1)
+        .take(p.len())
+        .chain(q.iter().cycle().skip(j + 2).take(q.len() - 2))
+        .cloned()
+        .collect();
+    let len = merged.len();
+
+    (0..len)
+        .all(|k| {
+            turn(
+                points[merged[(k + len - 1) % len]],
+                points[merged[k]],
+                points[merged[(k + 1) % len]],
+            ) >= -EPSILON
+        })
+        .then_some(merged)
+}
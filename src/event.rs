@@ -0,0 +1,46 @@
+use crate::contact::ContactManifold;
+use hex::Id;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionState {
+    Started,
+    Persisted,
+    Ended,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionEvent {
+    pub state: CollisionState,
+    pub entity: Id,
+    pub other: Id,
+    pub child: usize,
+    pub other_child: usize,
+    pub sensor: bool,
+    pub manifold: Option<ContactManifold>,
+}
+
+impl CollisionEvent {
+    pub fn new(
+        state: CollisionState,
+        entity: Id,
+        other: Id,
+        child: usize,
+        other_child: usize,
+        sensor: bool,
+        manifold: Option<ContactManifold>,
+    ) -> Self {
+        Self {
+            state,
+            entity,
+            other,
+            child,
+            other_child,
+            sensor,
+            manifold,
+        }
+    }
+
+    pub fn involves(&self, entity: Id) -> bool {
+        self.entity == entity || self.other == entity
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    contact::{ClosestPoints, ContactManifold},
+    narrowphase::{self, Convex},
+};
+use hex::nalgebra::Vector2;
+
+const MAX_ITERATIONS: usize = 32;
+const TOLERANCE: f32 = 1e-5;
+
+// A closest point on each core.
+type Witness = (Vector2<f32>, Vector2<f32>);
+
+// A point of the Minkowski difference `a - b` with the points of `a` and `b`
+// that produced it.
+#[derive(Clone, Copy)]
+struct Vertex {
+    w: Vector2<f32>,
+    a: Vector2<f32>,
+    b: Vector2<f32>,
+}
+
+fn support(a: &Convex, b: &Convex, direction: Vector2<f32>) -> Option<Vertex> {
+    let farthest = |points: &[Vector2<f32>], direction: Vector2<f32>| {
+        points
+            .iter()
+            .cloned()
+            .max_by(|p, p2| p.dot(&direction).total_cmp(&p2.dot(&direction)))
+    };
+    let (a, b) = (
+        farthest(&a.points, direction)?,
+        farthest(&b.points, -direction)?,
+    );
+
+    Some(Vertex { w: a - b, a, b })
+}
+
+pub fn distance(a: &Convex, b: &Convex) -> Option<ClosestPoints> {
+    let radius = a.radius + b.radius;
+    let (distance, normal, p, p2) = match gjk(a, b)? {
+        Ok((p, p2)) => {
+            let distance = (p2 - p).magnitude();
+
+            if distance <= TOLERANCE {
+                return overlap(a, b);
+            }
+
+            (distance, (p2 - p) / distance, p, p2)
+        }
+        Err(simplex) => {
+            let Some((normal, depth, (p, p2))) = epa(a, b, simplex) else {
+                return overlap(a, b);
+            };
+
+            (-depth, normal, p, p2)
+        }
+    };
+
+    Some(ClosestPoints {
+        distance: distance - radius,
+        normal,
+        point: p + normal * a.radius,
+        other_point: p2 - normal * b.radius,
+        child: 0,
+        other_child: 0,
+    })
+}
+
+// EPA can't expand a degenerate difference, e.g. concentric circles or a
+// circle centred on a capsule's segment, so SAT finds the depth and normal.
+// The point on `b` is its support against the normal, the deepest into `a`,
+// taken at the middle of a face that lies flat against it.
+fn overlap(a: &Convex, b: &Convex) -> Option<ClosestPoints> {
+    let manifold = narrowphase::convex(a, b)?;
+    let normal = manifold.normal;
+    let deepest = b
+        .points
+        .iter()
+        .map(|p| p.dot(&normal))
+        .fold(f32::INFINITY, f32::min);
+    let support: Vec<_> = b
+        .points
+        .iter()
+        .cloned()
+        .filter(|p| p.dot(&normal) - deepest <= TOLERANCE)
+        .collect();
+    let other_point = narrowphase::centroid(&support) - normal * b.radius;
+
+    Some(ClosestPoints {
+        distance: -manifold.depth,
+        normal,
+        point: other_point + normal * manifold.depth,
+        other_point,
+        child: 0,
+        other_child: 0,
+    })
+}
+
+// The GJK/EPA counterpart of `narrowphase::convex`.
+pub fn convex(a: &Convex, b: &Convex) -> Option<ContactManifold> {
+    let closest = distance(a, b)?;
+
+    if closest.distance > 0.0 {
+        return None;
+    }
+
+    narrowphase::manifold(a, b, closest.normal, -closest.distance)
+}
+
+// Closest points of two disjoint cores, or the final simplex if they overlap.
+fn gjk(a: &Convex, b: &Convex) -> Option<Result<Witness, Vec<Vertex>>> {
+    let start = narrowphase::centroid(&b.points) - narrowphase::centroid(&a.points);
+    let mut simplex = vec![support(a, b, -start)?];
+    let mut closest = (simplex[0].w, vec![1.0]);
+
+    for _ in 0..MAX_ITERATIONS {
+        let v = closest.0;
+
+        if v.magnitude_squared() <= TOLERANCE * TOLERANCE {
+            return Some(Err(simplex));
+        }
+
+        let w = support(a, b, -v)?;
+
+        if v.magnitude_squared() - v.dot(&w.w) <= TOLERANCE * v.magnitude_squared().max(1.0)
+            || simplex.iter().any(|s| s.w == w.w)
+        {
+            break;
+        }
+
+        simplex.push(w);
+
+        if simplex.len() == 3 && contains_origin(&simplex) {
+            return Some(Err(simplex));
+        }
+
+        let (v, weights, kept) = reduce(&simplex);
+
+        simplex = kept.into_iter().map(|i| simplex[i]).collect();
+        closest = (v, weights);
+    }
+
+    let (p, p2) = simplex
+        .iter()
+        .zip(&closest.1)
+        .fold((Vector2::zeros(), Vector2::zeros()), |(p, p2), (s, l)| {
+            (p + s.a * *l, p2 + s.b * *l)
+        });
+
+    Some(Ok((p, p2)))
+}
+
+fn contains_origin(simplex: &[Vertex]) -> bool {
+    let [a, b, c] = [simplex[0].w, simplex[1].w, simplex[2].w];
+    let sides = [a.perp(&b), b.perp(&c), c.perp(&a)];
+
+    sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+}
+
+// The point of the simplex closest to the origin, its barycentric weights and
+// the vertices that support it.
+fn reduce(simplex: &[Vertex]) -> (Vector2<f32>, Vec<f32>, Vec<usize>) {
+    let segment = |i: usize, j: usize| {
+        let (a, b) = (simplex[i].w, simplex[j].w);
+        let ab = b - a;
+        let t = if ab.magnitude_squared() <= f32::EPSILON {
+            0.0
+        } else {
+            (-a.dot(&ab) / ab.magnitude_squared()).clamp(0.0, 1.0)
+        };
+
+        match t {
+            t if t <= 0.0 => (a, vec![1.0], vec![i]),
+            t if t >= 1.0 => (b, vec![1.0], vec![j]),
+            t => (a + ab * t, vec![1.0 - t, t], vec![i, j]),
+        }
+    };
+
+    match simplex.len() {
+        1 => (simplex[0].w, vec![1.0], vec![0]),
+        2 => segment(0, 1),
+        _ => [segment(0, 1), segment(1, 2), segment(2, 0)]
+            .into_iter()
+            .min_by(|(v, _, _), (v2, _, _)| {
+                v.magnitude_squared().total_cmp(&v2.magnitude_squared())
+            })
+            .unwrap_or_else(|| (simplex[0].w, vec![1.0], vec![0])),
+    }
+}
+
+// Expands the simplex toward the boundary of `a - b` to find the penetration
+// normal and depth of the cores.
+fn epa(a: &Convex, b: &Convex, mut polytope: Vec<Vertex>) -> Option<(Vector2<f32>, f32, Witness)> {
+    for direction in [Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0)] {
+        while polytope.len() < 3 {
+            let (v, v2) = (support(a, b, direction)?, support(a, b, -direction)?);
+            let fresh: Vec<_> = [v, v2]
+                .into_iter()
+                .filter(|v| polytope.iter().all(|p| (p.w - v.w).magnitude() > TOLERANCE))
+                .collect();
+
+            if fresh.is_empty() {
+                break;
+            }
+
+            polytope.extend(fresh.into_iter().take(3 - polytope.len()));
+        }
+    }
+
+    let area = (0..polytope.len())
+        .map(|i| polytope[i].w.perp(&polytope[(i + 1) % polytope.len()].w))
+        .sum::<f32>();
+
+    if polytope.len() < 3 || area.abs() <= TOLERANCE {
+        return None;
+    }
+
+    if area < 0.0 {
+        polytope.reverse();
+    }
+
+    for _ in 0..MAX_ITERATIONS {
+        let (i, normal, depth) = (0..polytope.len())
+            .filter_map(|i| {
+                let (p, p2) = (polytope[i].w, polytope[(i + 1) % polytope.len()].w);
+                let normal = Vector2::new(p2.y - p.y, p.x - p2.x).try_normalize(f32::EPSILON)?;
+
+                Some((i, normal, normal.dot(&p)))
+            })
+            .min_by(|(_, _, d), (_, _, d2)| d.total_cmp(d2))?;
+        let w = support(a, b, normal)?;
+
+        if w.w.dot(&normal) - depth <= TOLERANCE {
+            let (v, v2) = (polytope[i], polytope[(i + 1) % polytope.len()]);
+            let edge = v2.w - v.w;
+            let t = (-v.w.dot(&edge) / edge.magnitude_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+
+            return Some((
+                normal,
+                depth,
+                (v.a + (v2.a - v.a) * t, v.b + (v2.b - v.b) * t),
+            ));
+        }
+
+        polytope.insert(i + 1, w);
+    }
+
+    None
+}
@@ -1,2 +1,13 @@
+pub mod aabb;
+pub mod broadphase;
 pub mod components;
+pub mod contact;
+pub mod event;
+pub mod query;
+pub mod shape;
 pub mod systems;
+pub mod tilemap;
+
+mod decomposition;
+mod gjk;
+mod narrowphase;
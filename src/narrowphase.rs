@@ -0,0 +1,477 @@
+use crate::{aabb::Aabb, contact::ContactManifold};
+use hex::nalgebra::Vector2;
+
+const MAX_ITERATIONS: usize = 32;
+const TOLERANCE: f32 = 1e-3;
+
+pub type Adjacent = (Option<Vector2<f32>>, Option<Vector2<f32>>);
+
+// A world-space convex core (a point, segment or polygon) inflated by `radius`.
+// One-sided segments carry their neighbouring vertices in `adjacent`.
+pub struct Convex {
+    pub points: Vec<Vector2<f32>>,
+    pub radius: f32,
+    pub adjacent: Option<Adjacent>,
+}
+
+impl Convex {
+    pub fn new(points: Vec<Vector2<f32>>, radius: f32) -> Self {
+        Self {
+            points,
+            radius,
+            adjacent: None,
+        }
+    }
+
+    pub fn edge(
+        a: Vector2<f32>,
+        b: Vector2<f32>,
+        prev: Option<Vector2<f32>>,
+        next: Option<Vector2<f32>>,
+    ) -> Self {
+        Self {
+            points: vec![a, b],
+            radius: 0.0,
+            adjacent: Some((prev, next)),
+        }
+    }
+
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(&self.points).map(|aabb| aabb.expand(self.radius))
+    }
+}
+
+// The normal points from `a` toward `b`; contact points lie on the surface of
+// the incident shape.
+pub fn convex(a: &Convex, b: &Convex) -> Option<ContactManifold> {
+    let radius = a.radius + b.radius;
+    let (normal, depth) = match sat(&a.points, &b.points) {
+        Some((normal, depth)) => (normal, depth + radius),
+        None => {
+            let (p, p2) = closest_points(&a.points, &b.points)?;
+            let distance = (p2 - p).magnitude();
+
+            if distance > radius {
+                return None;
+            }
+
+            let normal = (p2 - p)
+                .try_normalize(f32::EPSILON)
+                .or_else(|| (centroid(&b.points) - centroid(&a.points)).try_normalize(f32::EPSILON))
+                .unwrap_or(Vector2::new(0.0, 1.0));
+
+            (normal, radius - distance)
+        }
+    };
+
+    manifold(a, b, normal, depth)
+}
+
+// Applies one-sided edges and clips contact points for a normal and depth found
+// by either backend.
+pub fn manifold(
+    a: &Convex,
+    b: &Convex,
+    normal: Vector2<f32>,
+    depth: f32,
+) -> Option<ContactManifold> {
+    let (normal, depth) = match a.adjacent {
+        Some(adjacent) => one_sided(a, adjacent, b, normal, depth)?,
+        None => (normal, depth),
+    };
+    let (normal, depth) = match b.adjacent {
+        Some(adjacent) => {
+            let (normal, depth) = one_sided(b, adjacent, a, -normal, depth)?;
+
+            (-normal, depth)
+        }
+        None => (normal, depth),
+    };
+
+    Some(ContactManifold::new(normal, depth, clip(a, b, normal)))
+}
+
+// Separation between the surfaces with a witness point on each, or `None` if
+// they touch.
+pub fn distance(a: &Convex, b: &Convex) -> Option<(f32, Vector2<f32>, Vector2<f32>)> {
+    if sat(&a.points, &b.points).is_some() {
+        return None;
+    }
+
+    let (p, p2) = closest_points(&a.points, &b.points)?;
+    let normal = (p2 - p).try_normalize(f32::EPSILON)?;
+    let distance = (p2 - p).magnitude() - a.radius - b.radius;
+
+    (distance > 0.0).then(|| (distance, p + normal * a.radius, p2 - normal * b.radius))
+}
+
+// Conservative advancement of `a` along `translation` toward a resting `b`.
+// The distance between two convex shapes is convex in time, so each step lands
+// on or before the first contact.
+pub fn cast(a: &Convex, b: &Convex, translation: Vector2<f32>) -> Option<(f32, ContactManifold)> {
+    let mut t = 0.0;
+
+    for _ in 0..MAX_ITERATIONS {
+        let moved = Convex {
+            points: a.points.iter().map(|p| p + translation * t).collect(),
+            radius: a.radius,
+            adjacent: a.adjacent,
+        };
+        let Some((distance, p, p2)) = distance(&moved, b) else {
+            return Some((t, convex(&moved, b)?));
+        };
+        let normal = (p2 - p) / (p2 - p).magnitude().max(f32::EPSILON);
+
+        if distance <= TOLERANCE {
+            return Some((t, ContactManifold::new(normal, 0.0, vec![p2])));
+        }
+
+        let approach = translation.dot(&normal);
+
+        if approach <= 0.0 {
+            return None;
+        }
+
+        t += distance / approach;
+
+        if t > 1.0 {
+            return None;
+        }
+    }
+
+    None
+}
+
+pub fn contains(convex: &Convex, point: Vector2<f32>) -> bool {
+    let inside = convex.points.len() > 2
+        && edges(&convex.points).all(|(p1, _, normal)| normal.dot(&(point - p1)) <= 0.0);
+
+    inside
+        || segments(&convex.points)
+            .map(|(a, b)| (point - closest_on_segment(point, a, b)).magnitude())
+            .any(|distance| distance <= convex.radius)
+}
+
+// Distance along the unit `direction` to where the ray enters the shape, and
+// the surface normal there. Rays starting inside hit immediately, facing back.
+pub fn raycast(
+    convex: &Convex,
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    max: f32,
+) -> Option<(f32, Vector2<f32>)> {
+    let face = match convex.points[..] {
+        [a, b] if convex.adjacent.is_some() => Some(Vector2::new(a.y - b.y, b.x - a.x)),
+        _ => None,
+    };
+
+    if face.is_none() && contains(convex, origin) {
+        return Some((0.0, -direction));
+    }
+
+    let radius = convex.radius;
+    let faces = edges(&convex.points)
+        .filter(|(_, _, normal)| face.map(|f| normal.dot(&f) > 0.0).unwrap_or(true))
+        .filter(|(_, _, normal)| normal.dot(&direction) < 0.0)
+        .filter_map(|(p1, p2, normal)| {
+            let t = ray_segment(
+                origin,
+                direction,
+                p1 + normal * radius,
+                p2 + normal * radius,
+            )?;
+
+            Some((t, normal))
+        });
+    let corners = convex
+        .points
+        .iter()
+        .filter(|_| radius > 0.0)
+        .filter_map(|center| {
+            let t = ray_circle(origin, direction, *center, radius)?;
+
+            Some((t, (origin + direction * t - center) / radius))
+        });
+
+    faces
+        .chain(corners)
+        .filter(|(t, _)| *t <= max)
+        .min_by(|(t, _), (t2, _)| t.total_cmp(t2))
+}
+
+fn ray_segment(
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    a: Vector2<f32>,
+    b: Vector2<f32>,
+) -> Option<f32> {
+    let edge = b - a;
+    let denominator = direction.perp(&edge);
+
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let t = (a - origin).perp(&edge) / denominator;
+    let u = (a - origin).perp(&direction) / denominator;
+
+    (t >= 0.0 && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+fn ray_circle(
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    center: Vector2<f32>,
+    radius: f32,
+) -> Option<f32> {
+    let m = origin - center;
+    let b = m.dot(&direction);
+    let c = m.magnitude_squared() - radius * radius;
+    let discriminant = b * b - c;
+
+    if (c > 0.0 && b > 0.0) || discriminant < 0.0 {
+        None
+    } else {
+        Some((-b - discriminant.sqrt()).max(0.0))
+    }
+}
+
+// Edges only push toward their front, the left of `a -> b`. Away from a convex
+// corner the normal is snapped to the face, so seams between neighbours are
+// invisible to anything sliding over them.
+fn one_sided(
+    edge: &Convex,
+    (prev, next): Adjacent,
+    other: &Convex,
+    normal: Vector2<f32>,
+    depth: f32,
+) -> Option<(Vector2<f32>, f32)> {
+    let [a, b] = edge.points[..] else {
+        return Some((normal, depth));
+    };
+    let face = Vector2::new(a.y - b.y, b.x - a.x).try_normalize(f32::EPSILON)?;
+    let center = centroid(&other.points);
+
+    if face.dot(&(center - a)) < 0.0 {
+        return None;
+    }
+
+    let t = (center - a).dot(&(b - a)) / (b - a).magnitude_squared();
+    let corner = match (t, prev, next) {
+        (t, Some(prev), _) if t < 0.0 => Some(turn(prev, a, b)),
+        (t, _, Some(next)) if t > 1.0 => Some(turn(a, b, next)),
+        (t, _, _) if !(0.0..=1.0).contains(&t) => None,
+        _ => Some(0.0),
+    };
+
+    match corner {
+        Some(turn) if turn >= -f32::EPSILON && normal.dot(&face) < 1.0 - f32::EPSILON => {
+            let depth = edge.radius + other.radius
+                - other
+                    .points
+                    .iter()
+                    .map(|p| face.dot(&(p - a)))
+                    .fold(f32::INFINITY, f32::min);
+
+            (depth > 0.0).then_some((face, depth))
+        }
+        _ => Some((normal, depth)),
+    }
+}
+
+fn turn(a: Vector2<f32>, b: Vector2<f32>, c: Vector2<f32>) -> f32 {
+    (b - a).perp(&(c - b))
+}
+
+fn sat(points: &[Vector2<f32>], points2: &[Vector2<f32>]) -> Option<(Vector2<f32>, f32)> {
+    let mut min = None;
+
+    for axis in axes(points).chain(axes(points2)) {
+        let (a_min, a_max) = project(points, &axis)?;
+        let (b_min, b_max) = project(points2, &axis)?;
+
+        if a_max < b_min || b_max < a_min {
+            return None;
+        }
+
+        let (m, axis) = if a_max - b_min <= b_max - a_min {
+            (a_max - b_min, axis)
+        } else {
+            (b_max - a_min, -axis)
+        };
+
+        if min.map(|(_, min)| m < min).unwrap_or(true) {
+            min = Some((axis, m));
+        }
+    }
+
+    min
+}
+
+// Segments also need their own direction, or two collinear segments would never
+// be separated.
+fn axes(points: &[Vector2<f32>]) -> impl Iterator<Item = Vector2<f32>> + '_ {
+    let direction = match points {
+        [a, b] => (b - a).try_normalize(f32::EPSILON),
+        _ => None,
+    };
+
+    edges(points).map(|(_, _, normal)| normal).chain(direction)
+}
+
+fn project(points: &[Vector2<f32>], axis: &Vector2<f32>) -> Option<(f32, f32)> {
+    points.iter().fold(None, |acc, p| {
+        let projected = axis.dot(p);
+
+        Some(match acc {
+            Some((min, max)) => (f32::min(min, projected), f32::max(max, projected)),
+            None => (projected, projected),
+        })
+    })
+}
+
+// Yields every non-degenerate edge with its outward normal, whatever the
+// winding. A segment yields both of its faces.
+fn edges(
+    points: &[Vector2<f32>],
+) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>, Vector2<f32>)> + '_ {
+    let winding = if area(points) < 0.0 { -1.0 } else { 1.0 };
+
+    (0..points.len()).filter_map(move |i| {
+        let p1 = points[i];
+        let p2 = points[(i + 1) % points.len()];
+        let normal = Vector2::new(p2.y - p1.y, p1.x - p2.x).try_normalize(f32::EPSILON)?;
+
+        Some((p1, p2, normal * winding))
+    })
+}
+
+fn area(points: &[Vector2<f32>]) -> f32 {
+    (0..points.len())
+        .map(|i| points[i].perp(&points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        / 2.0
+}
+
+pub fn centroid(points: &[Vector2<f32>]) -> Vector2<f32> {
+    points.iter().cloned().sum::<Vector2<f32>>() / points.len().max(1) as f32
+}
+
+fn segments(points: &[Vector2<f32>]) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>)> + '_ {
+    let count = match points.len() {
+        1 | 2 => 1,
+        len => len,
+    };
+
+    (0..count).map(move |i| (points[i], points[(i + 1) % points.len()]))
+}
+
+pub fn closest_on_segment(p: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> Vector2<f32> {
+    let ab = b - a;
+    let length = ab.magnitude_squared();
+
+    if length <= f32::EPSILON {
+        a
+    } else {
+        a + ab * ((p - a).dot(&ab) / length).clamp(0.0, 1.0)
+    }
+}
+
+// Closest pair of points between two disjoint cores.
+fn closest_points(
+    points: &[Vector2<f32>],
+    points2: &[Vector2<f32>],
+) -> Option<(Vector2<f32>, Vector2<f32>)> {
+    let from_a = points
+        .iter()
+        .flat_map(|p| segments(points2).map(move |(a, b)| (*p, closest_on_segment(*p, a, b))));
+    let from_b = points2
+        .iter()
+        .flat_map(|p| segments(points).map(move |(a, b)| (closest_on_segment(*p, a, b), *p)));
+
+    from_a.chain(from_b).min_by(|(a, b), (a2, b2)| {
+        (b - a)
+            .magnitude_squared()
+            .total_cmp(&(b2 - a2).magnitude_squared())
+    })
+}
+
+fn best_edge(
+    points: &[Vector2<f32>],
+    direction: Vector2<f32>,
+) -> Option<(Vector2<f32>, Vector2<f32>, Vector2<f32>)> {
+    edges(points).max_by(|(_, _, a), (_, _, b)| a.dot(&direction).total_cmp(&b.dot(&direction)))
+}
+
+// Clips the incident edge against the side planes of the reference edge and
+// keeps the points within reach of the reference face.
+fn clip(a: &Convex, b: &Convex, normal: Vector2<f32>) -> Vec<Vector2<f32>> {
+    let support = || {
+        b.points
+            .iter()
+            .cloned()
+            .min_by(|p, p2| p.dot(&normal).total_cmp(&p2.dot(&normal)))
+            .map(|p| p - normal * b.radius)
+            .into_iter()
+            .collect()
+    };
+    let (Some(edge), Some(edge2)) = (best_edge(&a.points, normal), best_edge(&b.points, -normal))
+    else {
+        return match a.points[..] {
+            [p] if b.points.len() > 1 => vec![p + normal * a.radius],
+            _ => support(),
+        };
+    };
+    let (reference, incident, radius) = if edge.2.dot(&normal).abs() >= edge2.2.dot(&normal).abs() {
+        (edge, edge2, b.radius)
+    } else {
+        (edge2, edge, a.radius)
+    };
+    let direction = (reference.1 - reference.0).normalize();
+    let clipped = clip_segment(
+        vec![incident.0, incident.1],
+        direction,
+        direction.dot(&reference.0),
+    );
+    let clipped = clip_segment(clipped, -direction, -direction.dot(&reference.1));
+    let offset = reference.2.dot(&reference.0);
+    let reach = a.radius + b.radius + f32::EPSILON.sqrt();
+    let contacts: Vec<_> = clipped
+        .into_iter()
+        .filter(|p| reference.2.dot(p) - offset <= reach)
+        .map(|p| p - reference.2 * radius)
+        .collect();
+
+    if contacts.is_empty() {
+        support()
+    } else {
+        contacts
+    }
+}
+
+fn clip_segment(
+    points: Vec<Vector2<f32>>,
+    direction: Vector2<f32>,
+    offset: f32,
+) -> Vec<Vector2<f32>> {
+    let [p1, p2] = points[..] else {
+        return points;
+    };
+    let d1 = direction.dot(&p1) - offset;
+    let d2 = direction.dot(&p2) - offset;
+    let mut clipped = Vec::new();
+
+    if d1 >= 0.0 {
+        clipped.push(p1);
+    }
+
+    if d2 >= 0.0 {
+        clipped.push(p2);
+    }
+
+    if d1 * d2 < 0.0 {
+        clipped.push(p1 + (p2 - p1) * (d1 / (d1 - d2)));
+    }
+
+    clipped
+}
@@ -0,0 +1,71 @@
+use crate::components::Collider;
+use hex::{nalgebra::Vector2, Id};
+
+// Picks the colliders a query can see. Like collisions, a piece matches if its
+// category is in `mask`, it shares a layer with `layers` and neither side
+// ignores a layer of the other. Unlike collisions, empty `layers` skip the
+// shared layer check, so the default filter matches everything. Entities in
+// `exclude` are always skipped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryFilter {
+    pub layers: Vec<Id>,
+    pub ignore: Vec<Id>,
+    pub mask: u32,
+    pub exclude: Vec<Id>,
+}
+
+impl QueryFilter {
+    pub fn new(layers: Vec<Id>, ignore: Vec<Id>) -> Self {
+        Self {
+            layers,
+            ignore,
+            mask: u32::MAX,
+            exclude: Vec::new(),
+        }
+    }
+
+    pub fn matches(&self, entity: Id, collider: &Collider, child: usize) -> bool {
+        if self.exclude.contains(&entity) || collider.filter_of(child).category & self.mask == 0 {
+            return false;
+        }
+
+        if self.layers.is_empty() && self.ignore.is_empty() {
+            return true;
+        }
+
+        let layers = collider.layers_of(child);
+
+        (self.layers.is_empty() || self.layers.iter().any(|a| layers.contains(a)))
+            && !(self.ignore.iter().any(|a| layers.contains(a))
+                || collider.ignore.iter().any(|b| self.layers.contains(b)))
+    }
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
+// `fraction` is `distance` over the ray's `max_distance`, or `None` for a ray
+// without one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub entity: Id,
+    pub child: usize,
+    pub point: Vector2<f32>,
+    pub normal: Vector2<f32>,
+    pub distance: f32,
+    pub fraction: Option<f32>,
+}
+
+// `normal` is the surface normal of the hit collider, facing the cast shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeHit {
+    pub entity: Id,
+    pub child: usize,
+    pub other_child: usize,
+    pub point: Vector2<f32>,
+    pub normal: Vector2<f32>,
+    pub fraction: f32,
+}
@@ -0,0 +1,368 @@
+use crate::{
+    aabb::Aabb,
+    components::{CollisionFilter, Material},
+    decomposition,
+    narrowphase::Convex,
+    tilemap::Tilemap,
+};
+use hex::{
+    nalgebra::{Matrix3, Vector2, Vector3},
+    Id,
+};
+use std::{error::Error, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    TooFewPoints,
+    Degenerate,
+    SelfIntersecting,
+    Concave,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooFewPoints => write!(f, "polygon needs at least three points"),
+            Self::Degenerate => write!(f, "polygon has no area"),
+            Self::SelfIntersecting => write!(f, "polygon edges intersect each other"),
+            Self::Concave => write!(f, "polygon is not convex"),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Child {
+    pub shape: Shape,
+    pub offset: Vector2<f32>,
+    pub rotation: f32,
+    pub material: Option<Material>,
+    pub layers: Option<Vec<Id>>,
+    pub filter: Option<CollisionFilter>,
+}
+
+impl Child {
+    pub fn new(shape: Shape, offset: Vector2<f32>, rotation: f32) -> Self {
+        Self {
+            shape,
+            offset,
+            rotation,
+            material: None,
+            layers: None,
+            filter: None,
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix3<f32> {
+        Matrix3::new_translation(&self.offset) * Matrix3::new_rotation(self.rotation)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Polygon(Vec<Vector2<f32>>),
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+    },
+    Capsule {
+        a: Vector2<f32>,
+        b: Vector2<f32>,
+        radius: f32,
+    },
+    Edge {
+        a: Vector2<f32>,
+        b: Vector2<f32>,
+        prev: Option<Vector2<f32>>,
+        next: Option<Vector2<f32>>,
+    },
+    Chain {
+        points: Vec<Vector2<f32>>,
+        closed: bool,
+    },
+    Compound(Vec<Child>),
+    Tilemap(Tilemap),
+}
+
+impl Shape {
+    // Either winding is accepted; convex means every corner turns the same way.
+    pub fn polygon(points: Vec<Vector2<f32>>) -> Result<Self, ShapeError> {
+        Self::validate(&points)?;
+
+        let len = points.len();
+        let turns: Vec<_> = (0..len)
+            .map(|i| {
+                decomposition::turn(
+                    points[(i + len - 1) % len],
+                    points[i],
+                    points[(i + 1) % len],
+                )
+            })
+            .collect();
+
+        if turns.iter().all(|t| *t >= 0.0) || turns.iter().all(|t| *t <= 0.0) {
+            Ok(Self::Polygon(points))
+        } else {
+            Err(ShapeError::Concave)
+        }
+    }
+
+    // The counter-clockwise convex hull of a point cloud.
+    pub fn convex_hull(points: &[Vector2<f32>]) -> Result<Self, ShapeError> {
+        let hull = decomposition::hull(points);
+
+        match hull.len() {
+            0..=2 if points.len() < 3 => Err(ShapeError::TooFewPoints),
+            0..=2 => Err(ShapeError::Degenerate),
+            _ => Ok(Self::Polygon(hull)),
+        }
+    }
+
+    // Any simple polygon, split into a compound of convex pieces.
+    pub fn concave(mut points: Vec<Vector2<f32>>) -> Result<Self, ShapeError> {
+        if Self::validate(&points)? < 0.0 {
+            points.reverse();
+        }
+
+        Ok(Self::Compound(
+            decomposition::decompose(&points)
+                .into_iter()
+                .map(|piece| Child::new(Self::Polygon(piece), Vector2::zeros(), 0.0))
+                .collect(),
+        ))
+    }
+
+    // Checks the polygon is simple and returns its signed area.
+    fn validate(points: &[Vector2<f32>]) -> Result<f32, ShapeError> {
+        let len = points.len();
+
+        if len < 3 {
+            return Err(ShapeError::TooFewPoints);
+        }
+
+        for i in 0..len {
+            for j in (i + 2)..len {
+                if i == 0 && j == len - 1 {
+                    continue;
+                }
+
+                if decomposition::segments_intersect(
+                    points[i],
+                    points[(i + 1) % len],
+                    points[j],
+                    points[(j + 1) % len],
+                ) {
+                    return Err(ShapeError::SelfIntersecting);
+                }
+            }
+        }
+
+        let area = (0..len)
+            .map(|i| points[i].perp(&points[(i + 1) % len]))
+            .sum::<f32>()
+            / 2.0;
+
+        if area.abs() <= f32::EPSILON {
+            return Err(ShapeError::Degenerate);
+        }
+
+        Ok(area)
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle {
+            center: Vector2::zeros(),
+            radius,
+        }
+    }
+
+    // A vertical capsule `height` tall overall, caps included.
+    pub fn capsule(height: f32, radius: f32) -> Self {
+        let half = (height / 2.0 - radius).max(0.0);
+
+        Self::Capsule {
+            a: Vector2::new(0.0, -half),
+            b: Vector2::new(0.0, half),
+            radius,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            Self::Polygon(points) => points.iter().map(|p| p.magnitude()).fold(0.0, f32::max),
+            Self::Circle { center, radius } => center.magnitude() + radius,
+            Self::Capsule { a, b, radius } => a.magnitude().max(b.magnitude()) + radius,
+            Self::Edge { a, b, .. } => a.magnitude().max(b.magnitude()),
+            Self::Chain { points, .. } => points.iter().map(|p| p.magnitude()).fold(0.0, f32::max),
+            Self::Compound(children) => children
+                .iter()
+                .map(|c| c.offset.magnitude() + c.shape.radius())
+                .fold(0.0, f32::max),
+            Self::Tilemap(tilemap) => tilemap
+                .rects()
+                .map(|(_, r)| r.min.magnitude().max(r.max.magnitude()))
+                .fold(0.0, f32::max),
+        }
+    }
+
+    // A one-sided segment that only collides from its left, e.g. ground drawn
+    // left to right faces up.
+    pub fn edge(a: Vector2<f32>, b: Vector2<f32>) -> Self {
+        Self::Edge {
+            a,
+            b,
+            prev: None,
+            next: None,
+        }
+    }
+
+    // Chains are split into edges that know their neighbours, with one child
+    // index per segment.
+    pub fn chain(points: Vec<Vector2<f32>>, closed: bool) -> Self {
+        Self::Chain { points, closed }
+    }
+
+    // Flattens the shape into world-space convex pieces, each tagged with the
+    // index of the top-level child it belongs to.
+    pub(crate) fn leaves(&self, matrix: &Matrix3<f32>) -> Vec<(usize, Convex)> {
+        self.leaves_within(matrix, None)
+    }
+
+    // Like `leaves`, but tilemaps only yield the rectangles overlapping the
+    // world-space `bounds`, found through their grid.
+    pub(crate) fn leaves_within(
+        &self,
+        matrix: &Matrix3<f32>,
+        bounds: Option<&Aabb>,
+    ) -> Vec<(usize, Convex)> {
+        match self {
+            Self::Compound(children) => children
+                .iter()
+                .enumerate()
+                .flat_map(|(i, c)| {
+                    c.shape
+                        .leaves_within(&(matrix * c.matrix()), bounds)
+                        .into_iter()
+                        .map(move |(_, convex)| (i, convex))
+                })
+                .collect(),
+            Self::Chain { points, closed } => {
+                let len = points.len();
+                let count = match (len, closed) {
+                    (0 | 1, _) => 0,
+                    (2, _) | (_, false) => len - 1,
+                    _ => len,
+                };
+                let at = |i: isize| {
+                    let i = if *closed {
+                        i.rem_euclid(len as isize)
+                    } else {
+                        i
+                    };
+
+                    points.get(usize::try_from(i).ok()?).cloned()
+                };
+
+                (0..count)
+                    .filter_map(|i| {
+                        let i = i as isize;
+                        let edge = Self::Edge {
+                            a: at(i)?,
+                            b: at(i + 1)?,
+                            prev: at(i - 1),
+                            next: at(i + 2),
+                        };
+
+                        Some((i as usize, edge.convex(matrix)))
+                    })
+                    .collect()
+            }
+            Self::Tilemap(tilemap) => {
+                let local = bounds.and_then(|b| {
+                    let inverse = matrix.try_inverse()?;
+
+                    Self::transform_aabb(&inverse, b)
+                });
+                let rects = match local {
+                    Some(local) => tilemap.rects_in(&local),
+                    None => tilemap.rects().collect(),
+                };
+
+                rects
+                    .into_iter()
+                    .map(|(i, r)| {
+                        let rect = Self::Polygon(vec![
+                            r.min,
+                            Vector2::new(r.max.x, r.min.y),
+                            r.max,
+                            Vector2::new(r.min.x, r.max.y),
+                        ]);
+
+                        (i, rect.convex(matrix))
+                    })
+                    .collect()
+            }
+            _ => vec![(0, self.convex(matrix))],
+        }
+    }
+
+    // Tilemaps use their whole grid so no rectangle has to be visited.
+    pub(crate) fn aabb(&self, matrix: &Matrix3<f32>) -> Option<Aabb> {
+        match self {
+            Self::Compound(children) => children
+                .iter()
+                .filter_map(|c| c.shape.aabb(&(matrix * c.matrix())))
+                .reduce(|a, b| a.merge(&b)),
+            Self::Tilemap(tilemap) => Self::transform_aabb(matrix, &tilemap.bounds()?),
+            _ => self
+                .leaves(matrix)
+                .iter()
+                .filter_map(|(_, convex)| convex.aabb())
+                .reduce(|a, b| a.merge(&b)),
+        }
+    }
+
+    fn convex(&self, matrix: &Matrix3<f32>) -> Convex {
+        let point = |p: &Vector2<f32>| (matrix * Vector3::new(p.x, p.y, 1.0)).xy();
+
+        match self {
+            Self::Polygon(points) => Convex::new(points.iter().map(point).collect(), 0.0),
+            Self::Circle { center, radius } => {
+                Convex::new(vec![point(center)], radius * Self::scale(matrix))
+            }
+            Self::Capsule { a, b, radius } => {
+                Convex::new(vec![point(a), point(b)], radius * Self::scale(matrix))
+            }
+            Self::Edge { a, b, prev, next } => Convex::edge(
+                point(a),
+                point(b),
+                prev.as_ref().map(point),
+                next.as_ref().map(point),
+            ),
+            Self::Chain { .. } | Self::Compound(_) | Self::Tilemap(_) => {
+                Convex::new(Vec::new(), 0.0)
+            }
+        }
+    }
+
+    fn transform_aabb(matrix: &Matrix3<f32>, aabb: &Aabb) -> Option<Aabb> {
+        let corners = [
+            aabb.min,
+            Vector2::new(aabb.max.x, aabb.min.y),
+            aabb.max,
+            Vector2::new(aabb.min.x, aabb.max.y),
+        ]
+        .map(|p| (matrix * Vector3::new(p.x, p.y, 1.0)).xy());
+
+        Aabb::from_points(&corners)
+    }
+
+    // Rounded shapes stay round, so they take the largest axis scale.
+    fn scale(matrix: &Matrix3<f32>) -> f32 {
+        matrix
+            .transform_vector(&Vector2::new(1.0, 0.0))
+            .magnitude()
+            .max(matrix.transform_vector(&Vector2::new(0.0, 1.0)).magnitude())
+    }
+}
@@ -1,15 +1,755 @@
-use crate::components::Collider;
+use crate::{
+    aabb::Aabb,
+    broadphase::{AabbTree, Broadphase},
+    components::{BodyType, Collider, CollisionFilter, Material, Parent, RigidBody},
+    contact::{ContactManifold, Narrowphase},
+    event::{CollisionEvent, CollisionState},
+    query::{QueryFilter, RayHit, ShapeHit},
+    shape::Shape,
+};
 use hex::{
     anyhow,
     components::Trans,
+    nalgebra::{Matrix3, Vector2, Vector3},
     parking_lot::RwLock,
     winit::event::{Event, WindowEvent},
-    world::{system_manager::System, World},
-    Context, Control,
+    world::{entity_manager::EntityManager, system_manager::System, World},
+    Context, Control, Id,
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+const MAX_DEPTH: usize = 32;
+
+// Shapes are baked with the full world matrix, parents included.
+fn world_matrix(em: &EntityManager, mut e: Id) -> Option<Matrix3<f32>> {
+    let mut matrix = em.get_component::<Trans>(e)?.read().matrix();
+
+    for _ in 0..MAX_DEPTH {
+        let Some(parent) = em.get_component::<Parent>(e) else {
+            break;
+        };
+
+        e = parent.read().entity;
+        matrix = em.get_component::<Trans>(e)?.read().matrix() * matrix;
+    }
+
+    Some(matrix)
+}
+
+struct Body {
+    entity: Id,
+    collider: Option<Arc<RwLock<Collider>>>,
+    transform: Arc<RwLock<Trans>>,
+    rigid_body: Option<Arc<RwLock<RigidBody>>>,
+    matrix: Matrix3<f32>,
+    aabb: Option<Aabb>,
+    body_type: BodyType,
+    inverse_mass: f32,
+    inverse_inertia: f32,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    angular_velocity: f32,
+}
+
+impl Body {
+    fn velocity_at(&self, offset: Vector2<f32>) -> Vector2<f32> {
+        self.velocity + Vector2::new(-offset.y, offset.x) * self.angular_velocity
+    }
+
+    // Bodies without a `RigidBody` have nowhere to store velocity, so the
+    // solver treats them as immovable.
+    fn velocity_inverse_mass(&self) -> f32 {
+        if self.rigid_body.is_some() {
+            self.inverse_mass
+        } else {
+            0.0
+        }
+    }
+
+    fn velocity_inverse_inertia(&self) -> f32 {
+        if self.rigid_body.is_some() {
+            self.inverse_inertia
+        } else {
+            0.0
+        }
+    }
+
+    fn apply_impulse(&mut self, impulse: Vector2<f32>, offset: Vector2<f32>) {
+        self.velocity += impulse * self.velocity_inverse_mass();
+        self.angular_velocity += offset.perp(&impulse) * self.velocity_inverse_inertia();
+    }
+}
+
+struct ContactPoint {
+    offset: Vector2<f32>,
+    offset2: Vector2<f32>,
+    normal_mass: f32,
+    tangent_mass: f32,
+    bias: f32,
+    normal_impulse: f32,
+    tangent_impulse: f32,
+}
+
+struct Contact {
+    a: usize,
+    b: usize,
+    manifold: ContactManifold,
+    restitution: f32,
+    friction: f32,
+    points: Vec<ContactPoint>,
+    sensor: bool,
+}
+
+pub struct PhysicsManager {
+    pub gravity: Vector2<f32>,
+    pub timestep: f32,
+    pub max_substeps: u32,
+    pub velocity_iterations: u32,
+    pub restitution_threshold: f32,
+    pub slop: f32,
+    pub correction: f32,
+    pub broadphase: Box<dyn Broadphase>,
+    pub narrowphase: Narrowphase,
+    pub sensor_vs_sensor: bool,
+    pub alpha: Arc<RwLock<f32>>,
+    pub manifolds: Arc<RwLock<Vec<(Id, Id, ContactManifold)>>>,
+    pub events: Arc<RwLock<Vec<CollisionEvent>>>,
+    touching: HashMap<(Id, usize, Id, usize), bool>,
+    stale: bool,
+    accumulator: f32,
+    last_update: Option<Instant>,
+}
+
+impl PhysicsManager {
+    pub fn new(gravity: Vector2<f32>, timestep: f32, max_substeps: u32) -> Self {
+        Self {
+            gravity,
+            timestep,
+            max_substeps,
+            velocity_iterations: 8,
+            restitution_threshold: 1.0,
+            slop: 0.01,
+            correction: 0.8,
+            broadphase: Box::new(AabbTree::default()),
+            narrowphase: Narrowphase::default(),
+            sensor_vs_sensor: false,
+            alpha: Arc::new(RwLock::new(0.0)),
+            manifolds: Arc::new(RwLock::new(Vec::new())),
+            events: Arc::new(RwLock::new(Vec::new())),
+            touching: HashMap::new(),
+            stale: true,
+            accumulator: 0.0,
+            last_update: None,
+        }
+    }
+
+    // Runs as many fixed steps as `delta` seconds allow, up to `max_substeps`.
+    // Time left over past the cap is dropped so a long stall can't snowball.
+    // Nothing runs while `timestep` isn't positive and finite, and bad deltas
+    // are ignored, so the accumulator can never turn NaN.
+    pub fn advance(&mut self, world: &World, delta: f32) -> u32 {
+        let mut steps = 0;
+
+        if !(self.timestep > 0.0 && self.timestep.is_finite()) {
+            return steps;
+        }
+
+        if delta > 0.0 && delta.is_finite() {
+            self.accumulator += delta;
+        }
+
+        while self.accumulator >= self.timestep && steps < self.max_substeps {
+            self.step(world);
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+
+        if self.accumulator >= self.timestep {
+            self.accumulator %= self.timestep;
+        }
+
+        *self.alpha.write() = self.accumulator / self.timestep;
+
+        steps
+    }
+
+    // Runs a single step of `timestep` seconds.
+    pub fn step(&mut self, world: &World) {
+        let em = world.em.clone();
+        let em = em.read();
+        let mut bodies: Vec<_> = em
+            .entities()
+            .filter_map(|e| {
+                let collider = em.get_component::<Collider>(e);
+                // Parented colliders follow their parent, so the solver must
+                // treat them as immovable whatever their body type, and any
+                // `RigidBody` on them is left alone (see `Parent`).
+                let parented = em.get_component::<Parent>(e).is_some();
+                let rigid_body = em.get_component::<RigidBody>(e).filter(|_| !parented);
+                let body_type = collider
+                    .as_ref()
+                    .map(|c| c.read().body_type)
+                    .unwrap_or_default();
+                let (inverse_mass, inverse_inertia) = match body_type {
+                    BodyType::Dynamic if !parented => rigid_body
+                        .as_ref()
+                        .map(|b| {
+                            let b = b.read();
+
+                            (b.inverse_mass(), b.inverse_inertia())
+                        })
+                        .unwrap_or((1.0, 0.0)),
+                    _ => (0.0, 0.0),
+                };
+                let transform = em.get_component::<Trans>(e)?;
+                let matrix = world_matrix(&em, e)?;
+                let position = (matrix * Vector3::new(0.0, 0.0, 1.0)).xy();
+                let aabb = collider.as_ref().and_then(|c| c.read().aabb_at(&matrix));
 
-pub struct PhysicsManager;
+                Some(Body {
+                    entity: e,
+                    collider,
+                    transform,
+                    rigid_body,
+                    matrix,
+                    aabb,
+                    body_type,
+                    inverse_mass,
+                    inverse_inertia,
+                    position,
+                    velocity: Vector2::zeros(),
+                    angular_velocity: 0.0,
+                })
+            })
+            .collect();
+
+        for body in &mut bodies {
+            if let Some(b) = &body.rigid_body {
+                let b = &mut *b.write();
+
+                b.integrate_velocity(body.body_type, self.gravity, self.timestep);
+                body.velocity = b.velocity;
+                body.angular_velocity = b.angular_velocity;
+            }
+        }
+
+        let (sensors, mut contacts): (Vec<_>, Vec<_>) =
+            self.contacts(&bodies).into_iter().partition(|c| c.sensor);
+
+        self.solve_velocities(&mut bodies, &mut contacts);
+
+        for body in &bodies {
+            if let Some(b) = &body.rigid_body {
+                let b = &mut *b.write();
+
+                b.velocity = body.velocity;
+                b.angular_velocity = body.angular_velocity;
+                b.integrate_position(&mut body.transform.write(), body.body_type, self.timestep);
+            }
+        }
+
+        self.correct_positions(&bodies, &contacts);
+        self.emit_events(&bodies, contacts.iter().chain(&sensors));
+
+        // Bodies have moved since the broadphase saw them, so the next query
+        // refreshes it first.
+        self.stale = true;
+
+        *self.manifolds.write() = contacts
+            .into_iter()
+            .map(|c| (bodies[c.a].entity, bodies[c.b].entity, c.manifold))
+            .collect();
+    }
+
+    // Takes every event queued since the last drain, oldest first. Steps only
+    // ever append to `events`, so whoever consumes them must drain it.
+    pub fn drain_events(&self) -> Vec<CollisionEvent> {
+        std::mem::take(&mut *self.events.write())
+    }
+
+    pub fn raycast(
+        &mut self,
+        world: &World,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+        filter: &QueryFilter,
+    ) -> Option<RayHit> {
+        self.raycast_all(world, origin, direction, max_distance, filter)
+            .into_iter()
+            .next()
+    }
+
+    // Every collider the ray enters, nearest first. `max_distance` may be
+    // infinite; a ray that isn't positive, or has no direction, hits nothing.
+    pub fn raycast_all(
+        &mut self,
+        world: &World,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+        filter: &QueryFilter,
+    ) -> Vec<RayHit> {
+        let Some(direction) = direction.try_normalize(f32::EPSILON) else {
+            return Vec::new();
+        };
+
+        if max_distance.is_nan() || max_distance <= 0.0 {
+            return Vec::new();
+        }
+
+        // Axes the ray doesn't move along stay put even when it never ends.
+        let end = origin + direction.map(|d| if d == 0.0 { 0.0 } else { d * max_distance });
+        let mut hits: Vec<_> = self
+            .colliders(world, &Aabb::new(origin.inf(&end), origin.sup(&end)))
+            .into_iter()
+            .filter_map(|(entity, collider, matrix)| {
+                let collider = collider.read();
+                let (child, t, normal) = collider.raycast_filtered(
+                    &matrix,
+                    origin,
+                    direction,
+                    max_distance,
+                    |child| filter.matches(entity, &collider, child),
+                )?;
+
+                Some(RayHit {
+                    entity,
+                    child,
+                    point: origin + direction * t,
+                    normal,
+                    distance: t,
+                    fraction: max_distance.is_finite().then(|| t / max_distance),
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    // Sweeps `collider` by `translation` without moving anything and returns
+    // the first hit. Pieces are only hit if they interact with `collider` and
+    // match `filter`.
+    pub fn shape_cast(
+        &mut self,
+        world: &World,
+        collider: &Collider,
+        matrix: &Matrix3<f32>,
+        translation: Vector2<f32>,
+        filter: &QueryFilter,
+    ) -> Option<ShapeHit> {
+        let aabb = collider.aabb_at(matrix)?;
+        let swept = aabb.merge(&Aabb::new(aabb.min + translation, aabb.max + translation));
+
+        self.colliders(world, &swept)
+            .into_iter()
+            .filter_map(|(entity, c2, matrix2)| {
+                let c2 = c2.read();
+                let (fraction, manifold) = collider.cast_filtered(
+                    matrix,
+                    translation,
+                    &matrix2,
+                    &c2,
+                    |child, child2| {
+                        collider.interacts(child, &c2, child2)
+                            && filter.matches(entity, &c2, child2)
+                    },
+                )?;
+
+                Some(ShapeHit {
+                    entity,
+                    child: manifold.child,
+                    other_child: manifold.other_child,
+                    point: *manifold.points.first()?,
+                    normal: -manifold.normal,
+                    fraction,
+                })
+            })
+            .min_by(|a, b| a.fraction.total_cmp(&b.fraction))
+    }
+
+    // Entities with a piece containing `point`, e.g. for picking.
+    pub fn overlap_point(
+        &mut self,
+        world: &World,
+        point: Vector2<f32>,
+        filter: &QueryFilter,
+    ) -> Vec<Id> {
+        self.colliders(world, &Aabb::new(point, point))
+            .into_iter()
+            .filter(|(entity, collider, matrix)| {
+                let collider = collider.read();
+
+                collider.contains_filtered(matrix, point, |child| {
+                    filter.matches(*entity, &collider, child)
+                })
+            })
+            .map(|(entity, _, _)| entity)
+            .collect()
+    }
+
+    pub fn overlap_aabb(&mut self, world: &World, aabb: &Aabb, filter: &QueryFilter) -> Vec<Id> {
+        let rect = Shape::Polygon(vec![
+            aabb.min,
+            Vector2::new(aabb.max.x, aabb.min.y),
+            aabb.max,
+            Vector2::new(aabb.min.x, aabb.max.y),
+        ]);
+
+        self.overlap_shape(world, rect, filter)
+    }
+
+    pub fn overlap_circle(
+        &mut self,
+        world: &World,
+        center: Vector2<f32>,
+        radius: f32,
+        filter: &QueryFilter,
+    ) -> Vec<Id> {
+        self.overlap_shape(world, Shape::Circle { center, radius }, filter)
+    }
+
+    // Only the pieces found are filtered; the layers of `collider` itself are
+    // not consulted. Areas only need to intersect, so one-sided edges and
+    // chains are found from either side.
+    pub fn overlap_collider(
+        &mut self,
+        world: &World,
+        collider: &Collider,
+        matrix: &Matrix3<f32>,
+        filter: &QueryFilter,
+    ) -> Vec<Id> {
+        let Some(aabb) = collider.aabb_at(matrix) else {
+            return Vec::new();
+        };
+
+        self.colliders(world, &aabb)
+            .into_iter()
+            .filter(|(entity, c2, matrix2)| {
+                let c2 = c2.read();
+
+                !collider
+                    .contacts_filtered(
+                        matrix,
+                        matrix2,
+                        &c2,
+                        self.narrowphase,
+                        false,
+                        |_, child2| filter.matches(*entity, &c2, child2),
+                    )
+                    .is_empty()
+            })
+            .map(|(entity, _, _)| entity)
+            .collect()
+    }
+
+    fn overlap_shape(&mut self, world: &World, shape: Shape, filter: &QueryFilter) -> Vec<Id> {
+        let probe = Collider {
+            shape,
+            layers: Vec::new(),
+            ignore: Vec::new(),
+            body_type: BodyType::Static,
+            material: Material::default(),
+            filter: CollisionFilter::default(),
+            ghost: true,
+        };
+
+        self.overlap_collider(world, &probe, &Matrix3::identity(), filter)
+    }
+
+    // Colliders whose world AABB touches `aabb`, with their world matrices.
+    // Candidates come from the broadphase, which is refreshed on the first
+    // query after a step so queries between steps share one update. Colliders
+    // added or moved after that query are found after the next step.
+    fn colliders(
+        &mut self,
+        world: &World,
+        aabb: &Aabb,
+    ) -> Vec<(Id, Arc<RwLock<Collider>>, Matrix3<f32>)> {
+        let em = world.em.read();
+
+        if self.stale {
+            let proxies: Vec<_> = em
+                .entities()
+                .filter_map(|e| {
+                    let matrix = world_matrix(&em, e)?;
+
+                    Some((e, em.get_component::<Collider>(e)?.read().aabb_at(&matrix)?))
+                })
+                .collect();
+
+            self.broadphase.update(&proxies);
+            self.stale = false;
+        }
+
+        let mut candidates = self.broadphase.query(aabb);
+
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .filter_map(|e| {
+                let collider = em.get_component::<Collider>(e)?;
+                let matrix = world_matrix(&em, e)?;
+                let bounds = collider.read().aabb_at(&matrix)?;
+
+                bounds.intersects(aabb).then_some((e, collider, matrix))
+            })
+            .collect()
+    }
+
+    fn contacts(&mut self, bodies: &[Body]) -> Vec<Contact> {
+        let proxies: Vec<_> = bodies
+            .iter()
+            .filter_map(|b| Some((b.entity, b.aabb?)))
+            .collect();
+
+        self.broadphase.update(&proxies);
+        self.stale = false;
+
+        let indices: HashMap<_, _> = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.entity, i))
+            .collect();
+        let mut pairs: Vec<_> = self
+            .broadphase
+            .pairs()
+            .into_iter()
+            .filter_map(|(e, e2)| {
+                let (i, j) = (*indices.get(&e)?, *indices.get(&e2)?);
+
+                Some((i.min(j), i.max(j)))
+            })
+            .collect();
+
+        pairs.sort_unstable();
+
+        let mut contacts = Vec::new();
+
+        for (i, j) in pairs {
+            let (body, body2) = (&bodies[i], &bodies[j]);
+            let (Some(c), Some(c2)) = (&body.collider, &body2.collider) else {
+                continue;
+            };
+            let c = &*c.read();
+            let c2 = &*c2.read();
+
+            if c.body_type == BodyType::Static && c2.body_type == BodyType::Static {
+                continue;
+            }
+
+            // Ghosts are sensors: they report overlaps but never push back.
+            let sensor = c.ghost || c2.ghost;
+
+            if c.ghost && c2.ghost && !self.sensor_vs_sensor {
+                continue;
+            }
+
+            for manifold in c.contacts_filtered(
+                &body.matrix,
+                &body2.matrix,
+                c2,
+                self.narrowphase,
+                true,
+                |child, child2| c.interacts(child, c2, child2),
+            ) {
+                let material = c
+                    .material_of(manifold.child)
+                    .combine(&c2.material_of(manifold.other_child));
+
+                contacts.push(Contact {
+                    a: i,
+                    b: j,
+                    manifold,
+                    restitution: material.restitution,
+                    friction: material.friction,
+                    points: Vec::new(),
+                    sensor,
+                });
+            }
+        }
+
+        contacts
+    }
+
+    // Events name the lower entity first, flipping the manifold to match, and
+    // are reported once per pair of children: pieces of one child (e.g. a
+    // concave shape inside a compound) report their deepest manifold.
+    fn emit_events<'a>(&mut self, bodies: &[Body], contacts: impl Iterator<Item = &'a Contact>) {
+        let mut events = self.events.write();
+        let mut touching: HashMap<_, usize> = HashMap::new();
+        let mut current: Vec<CollisionEvent> = Vec::new();
+
+        for contact in contacts {
+            let (mut e, mut e2) = (bodies[contact.a].entity, bodies[contact.b].entity);
+            let mut manifold = contact.manifold.clone();
+
+            if e > e2 {
+                (e, e2) = (e2, e);
+                manifold.normal = -manifold.normal;
+                (manifold.child, manifold.other_child) = (manifold.other_child, manifold.child);
+            }
+
+            let key = (e, manifold.child, e2, manifold.other_child);
+
+            if let Some(&i) = touching.get(&key) {
+                let event = &mut current[i];
+
+                if event
+                    .manifold
+                    .as_ref()
+                    .is_some_and(|m| m.depth < manifold.depth)
+                {
+                    event.manifold = Some(manifold);
+                }
+
+                continue;
+            }
+
+            let state = if self.touching.contains_key(&key) {
+                CollisionState::Persisted
+            } else {
+                CollisionState::Started
+            };
+
+            touching.insert(key, current.len());
+            current.push(CollisionEvent::new(
+                state,
+                e,
+                e2,
+                key.1,
+                key.3,
+                contact.sensor,
+                Some(manifold),
+            ));
+        }
+
+        let mut ended: Vec<_> = self
+            .touching
+            .iter()
+            .filter(|(key, _)| !touching.contains_key(key))
+            .map(|(&key, &sensor)| (key, sensor))
+            .collect();
+
+        ended.sort_unstable_by_key(|(key, _)| *key);
+
+        self.touching = touching
+            .into_iter()
+            .map(|(key, i)| (key, current[i].sensor))
+            .collect();
+
+        events.extend(current);
+        events.extend(ended.into_iter().map(|((e, child, e2, child2), sensor)| {
+            CollisionEvent::new(CollisionState::Ended, e, e2, child, child2, sensor, None)
+        }));
+    }
+
+    fn solve_velocities(&self, bodies: &mut [Body], contacts: &mut [Contact]) {
+        for contact in contacts.iter_mut() {
+            let (a, b) = (&bodies[contact.a], &bodies[contact.b]);
+            let normal = contact.manifold.normal;
+            let tangent = Vector2::new(-normal.y, normal.x);
+            let mass = |offset: Vector2<f32>, offset2: Vector2<f32>, axis: Vector2<f32>| {
+                let k = a.velocity_inverse_mass()
+                    + b.velocity_inverse_mass()
+                    + a.velocity_inverse_inertia() * offset.perp(&axis).powi(2)
+                    + b.velocity_inverse_inertia() * offset2.perp(&axis).powi(2);
+
+                if k > 0.0 {
+                    1.0 / k
+                } else {
+                    0.0
+                }
+            };
+
+            contact.points = contact
+                .manifold
+                .points
+                .iter()
+                .map(|p| {
+                    let offset = p - a.position;
+                    let offset2 = p - b.position;
+                    let vn = (b.velocity_at(offset2) - a.velocity_at(offset)).dot(&normal);
+
+                    ContactPoint {
+                        offset,
+                        offset2,
+                        normal_mass: mass(offset, offset2, normal),
+                        tangent_mass: mass(offset, offset2, tangent),
+                        bias: if vn < -self.restitution_threshold {
+                            -contact.restitution * vn
+                        } else {
+                            0.0
+                        },
+                        normal_impulse: 0.0,
+                        tangent_impulse: 0.0,
+                    }
+                })
+                .collect();
+        }
+
+        for _ in 0..self.velocity_iterations {
+            for contact in contacts.iter_mut() {
+                let normal = contact.manifold.normal;
+                let tangent = Vector2::new(-normal.y, normal.x);
+
+                for point in &mut contact.points {
+                    let vn = (bodies[contact.b].velocity_at(point.offset2)
+                        - bodies[contact.a].velocity_at(point.offset))
+                    .dot(&normal);
+                    let previous = point.normal_impulse;
+
+                    point.normal_impulse =
+                        (previous + (point.bias - vn) * point.normal_mass).max(0.0);
+
+                    let impulse = normal * (point.normal_impulse - previous);
+
+                    bodies[contact.a].apply_impulse(-impulse, point.offset);
+                    bodies[contact.b].apply_impulse(impulse, point.offset2);
+
+                    let vt = (bodies[contact.b].velocity_at(point.offset2)
+                        - bodies[contact.a].velocity_at(point.offset))
+                    .dot(&tangent);
+                    let max = contact.friction * point.normal_impulse;
+                    let previous = point.tangent_impulse;
+
+                    point.tangent_impulse = (previous - vt * point.tangent_mass).clamp(-max, max);
+
+                    let impulse = tangent * (point.tangent_impulse - previous);
+
+                    bodies[contact.a].apply_impulse(-impulse, point.offset);
+                    bodies[contact.b].apply_impulse(impulse, point.offset2);
+                }
+            }
+        }
+    }
+
+    fn correct_positions(&self, bodies: &[Body], contacts: &[Contact]) {
+        for contact in contacts {
+            let (a, b) = (&bodies[contact.a], &bodies[contact.b]);
+            let total = a.inverse_mass + b.inverse_mass;
+
+            let ContactManifold { normal, depth, .. } = contact.manifold;
+
+            if total > 0.0 && depth > self.slop {
+                let correction = normal * (depth - self.slop) / total * self.correction;
+                let t = &mut *a.transform.write();
+                let t2 = &mut *b.transform.write();
+
+                t.set_position(t.position() - correction * a.inverse_mass);
+                t2.set_position(t2.position() + correction * b.inverse_mass);
+            }
+        }
+    }
+}
+
+impl Default for PhysicsManager {
+    fn default() -> Self {
+        Self::new(Vector2::zeros(), 1.0 / 60.0, 8)
+    }
+}
 
 impl System for PhysicsManager {
     fn update(
@@ -25,52 +765,15 @@ impl System for PhysicsManager {
                 event: WindowEvent::RedrawRequested,
                 window_id,
             } if window_id == { context.read().window.id() } => {
-                let em = world.read().em.clone();
-                let em = em.read();
-                let mut entities: Vec<_> = em
-                    .entities()
-                    .filter_map(|e| {
-                        Some((
-                            e,
-                            em.get_component::<Collider>(e)?,
-                            em.get_component::<Trans>(e)?,
-                        ))
-                    })
-                    .collect();
-
-                while let Some((e, c, t)) = entities.pop() {
-                    let t = &mut *t.write();
-                    let c = &mut *c.write();
-
-                    for (e2, c2, t2) in &entities {
-                        let c2 = &mut *c2.write();
-                        let t2 = &mut *t2.write();
-
-                        if c.layers.iter().any(|a| c2.layers.contains(a))
-                            && !(c.ignore.iter().any(|a| c2.layers.contains(a))
-                                || c2.ignore.iter().any(|b| c.layers.contains(b)))
-                            && (t.position() - t2.position()).magnitude()
-                                <= c.boundary + c2.boundary
-                        {
-                            if let Some(res) = c.intersecting(&t, &t2, &c2) {
-                                if !(c.ghost || c2.ghost) {
-                                    t.set_position(t.position() + res);
-                                    t2.set_position(t2.position() - res);
-
-                                    if c.log_collisions && !c.collisions.contains(e2) {
-                                        c.collisions.push(*e2);
-                                    }
-
-                                    if c2.log_collisions && !c2.collisions.contains(&e) {
-                                        c2.collisions.push(e);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+                let now = Instant::now();
+                let delta = self
+                    .last_update
+                    .replace(now)
+                    .map(|last| now.duration_since(last).as_secs_f32())
+                    .unwrap_or(0.0);
 
+                self.advance(&world.read(), delta);
+            }
             _ => {}
         }
 
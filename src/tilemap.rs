@@ -0,0 +1,274 @@
+use crate::aabb::Aabb;
+use hex::nalgebra::Vector2;
+use std::collections::HashSet;
+
+// Columns `start..end` over rows `bottom..top`, in tiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rect {
+    start: usize,
+    end: usize,
+    bottom: usize,
+    top: usize,
+}
+
+// A grid of solid tiles with tile (0, 0) in the bottom left corner at the
+// origin. Horizontal runs of solid tiles are cached per row and runs spanning
+// the same columns in consecutive rows are merged into one rectangle.
+//
+// Each rectangle keeps its index, which is the child index reported in
+// contacts and events, for as long as it exists. Edits only re-merge the rows
+// around the changed tile, so rectangles elsewhere are never touched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tilemap {
+    pub tile_size: Vector2<f32>,
+    width: usize,
+    height: usize,
+    solid: Vec<bool>,
+    runs: Vec<Vec<(usize, usize)>>,
+    rects: Vec<Option<Rect>>,
+    owners: Vec<Option<usize>>,
+    free: Vec<usize>,
+}
+
+impl Tilemap {
+    pub fn new(width: usize, height: usize, tile_size: Vector2<f32>) -> Self {
+        Self {
+            tile_size,
+            width,
+            height,
+            solid: vec![false; width * height],
+            runs: vec![Vec::new(); height],
+            rects: Vec::new(),
+            owners: vec![None; width * height],
+            free: Vec::new(),
+        }
+    }
+
+    // Rows are given bottom row first; short rows are padded with empty tiles.
+    pub fn from_grid(grid: &[Vec<bool>], tile_size: Vector2<f32>) -> Self {
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut tilemap = Self::new(width, grid.len(), tile_size);
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, solid) in row.iter().enumerate() {
+                tilemap.solid[y * width + x] = *solid;
+            }
+
+            tilemap.update_row(y);
+        }
+
+        let rects = tilemap.merge(0, tilemap.height);
+
+        tilemap.replace(Vec::new(), rects);
+        tilemap
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.solid[y * self.width + x]
+    }
+
+    // Recomputes the changed row's runs and re-merges only the rectangles in
+    // that row and the rows next to it. Returns whether the tile changed.
+    pub fn set(&mut self, x: usize, y: usize, solid: bool) -> bool {
+        if x >= self.width || y >= self.height || self.get(x, y) == solid {
+            return false;
+        }
+
+        self.solid[y * self.width + x] = solid;
+        self.update_row(y);
+
+        let rows = y.saturating_sub(1)..(y + 2).min(self.height);
+        let affected: HashSet<_> = rows
+            .flat_map(|row| &self.owners[row * self.width..(row + 1) * self.width])
+            .flatten()
+            .cloned()
+            .collect();
+        let mut affected: Vec<_> = affected.into_iter().collect();
+
+        affected.sort_unstable();
+
+        let (mut bottom, mut top) = (y, y + 1);
+
+        for i in &affected {
+            if let Some(rect) = self.rects[*i] {
+                bottom = bottom.min(rect.bottom);
+                top = top.max(rect.top);
+                self.claim(rect, None);
+            }
+        }
+
+        let rects = self.merge(bottom, top);
+
+        self.replace(affected, rects);
+
+        true
+    }
+
+    // Every rectangle with its child index, in local space.
+    pub fn rects(&self) -> impl Iterator<Item = (usize, Aabb)> + '_ {
+        self.rects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, rect)| Some((i, self.aabb(&(*rect)?))))
+    }
+
+    // The rectangles overlapping `aabb`, in local space. Only the tiles under
+    // `aabb` are visited.
+    pub fn rects_in(&self, aabb: &Aabb) -> Vec<(usize, Aabb)> {
+        let min = aabb.min.component_div(&self.tile_size);
+        let max = aabb.max.component_div(&self.tile_size);
+        let clamp = |v: f32, len: usize| (v.max(0.0) as usize).min(len);
+        let (x1, x2) = (
+            clamp(min.x.floor(), self.width),
+            clamp(max.x.ceil(), self.width),
+        );
+        let (y1, y2) = (
+            clamp(min.y.floor(), self.height),
+            clamp(max.y.ceil(), self.height),
+        );
+        let mut found = Vec::new();
+
+        // Inverted or NaN bounds cover no tiles.
+        if x1 >= x2 || y1 >= y2 {
+            return Vec::new();
+        }
+
+        for y in y1..y2 {
+            for owner in self.owners[y * self.width + x1..y * self.width + x2]
+                .iter()
+                .flatten()
+            {
+                if !found.contains(owner) {
+                    found.push(*owner);
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found
+            .into_iter()
+            .filter_map(|i| Some((i, self.aabb(&self.rects[i]?))))
+            .filter(|(_, rect)| rect.intersects(aabb))
+            .collect()
+    }
+
+    // The whole grid in local space, or `None` if no tile is solid.
+    pub fn bounds(&self) -> Option<Aabb> {
+        (self.rects.len() > self.free.len()).then(|| {
+            self.aabb(&Rect {
+                start: 0,
+                end: self.width,
+                bottom: 0,
+                top: self.height,
+            })
+        })
+    }
+
+    fn update_row(&mut self, y: usize) {
+        let row = &self.solid[y * self.width..(y + 1) * self.width];
+        let mut runs = Vec::new();
+        let mut start = None;
+
+        for (x, solid) in row.iter().chain([&false]).enumerate() {
+            match (start, solid) {
+                (None, true) => start = Some(x),
+                (Some(s), false) => {
+                    runs.push((s, x));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        self.runs[y] = runs;
+    }
+
+    // Merges the runs of rows `bottom..top` that no rectangle covers yet.
+    fn merge(&self, bottom: usize, top: usize) -> Vec<Rect> {
+        let mut open: Vec<((usize, usize), usize)> = Vec::new();
+        let mut rects = Vec::new();
+
+        for y in bottom..=top {
+            let runs: Vec<_> = self
+                .runs
+                .get(y)
+                .filter(|_| y < top)
+                .into_iter()
+                .flatten()
+                .filter(|(start, _)| self.owners[y * self.width + start].is_none())
+                .cloned()
+                .collect();
+            let (kept, closed): (Vec<_>, Vec<_>) =
+                open.into_iter().partition(|(run, _)| runs.contains(run));
+
+            rects.extend(closed.into_iter().map(|((start, end), bottom)| Rect {
+                start,
+                end,
+                bottom,
+                top: y,
+            }));
+            open = runs
+                .iter()
+                .map(|run| {
+                    kept.iter()
+                        .find(|(r, _)| r == run)
+                        .cloned()
+                        .unwrap_or((*run, y))
+                })
+                .collect();
+        }
+
+        rects
+    }
+
+    // Rectangles that come out of a merge unchanged keep their old index; the
+    // rest take freed indices first.
+    fn replace(&mut self, mut removed: Vec<usize>, rects: Vec<Rect>) {
+        let mut added = Vec::new();
+
+        for rect in rects {
+            match removed.iter().position(|i| self.rects[*i] == Some(rect)) {
+                Some(k) => {
+                    self.claim(rect, Some(removed.remove(k)));
+                }
+                None => added.push(rect),
+            }
+        }
+
+        for i in removed {
+            self.rects[i] = None;
+            self.free.push(i);
+        }
+
+        for rect in added {
+            let i = self.free.pop().unwrap_or_else(|| {
+                self.rects.push(None);
+                self.rects.len() - 1
+            });
+
+            self.rects[i] = Some(rect);
+            self.claim(rect, Some(i));
+        }
+    }
+
+    fn claim(&mut self, rect: Rect, owner: Option<usize>) {
+        for y in rect.bottom..rect.top {
+            self.owners[y * self.width + rect.start..y * self.width + rect.end].fill(owner);
+        }
+    }
+
+    fn aabb(&self, rect: &Rect) -> Aabb {
+        Aabb::new(
+            Vector2::new(rect.start as f32, rect.bottom as f32).component_mul(&self.tile_size),
+            Vector2::new(rect.end as f32, rect.top as f32).component_mul(&self.tile_size),
+        )
+    }
+}
@@ -0,0 +1,77 @@
+use hex::{nalgebra::Vector2, Id};
+use hex_physics::{
+    aabb::Aabb,
+    broadphase::{AabbTree, Broadphase, BruteForce, SpatialHash, SweepAndPrune},
+};
+
+fn random(seed: &mut u64) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+
+    (*seed % 10_000) as f32 / 10_000.0
+}
+
+fn sorted(pairs: Vec<(Id, Id)>) -> Vec<(Id, Id)> {
+    let mut pairs: Vec<_> = pairs
+        .into_iter()
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+
+    pairs.sort_unstable();
+    pairs
+}
+
+#[test]
+fn backends_agree_with_brute_force() {
+    let mut seed = 0x1234_5678_9abc_def1;
+    let mut proxies: Vec<(Id, Aabb)> = (0..300)
+        .map(|i| {
+            let min = Vector2::new(random(&mut seed), random(&mut seed)) * 40.0;
+            let extents =
+                Vector2::new(random(&mut seed) * 8.0 + 0.1, random(&mut seed) * 0.5 + 0.1);
+
+            (i, Aabb::new(min, min + extents))
+        })
+        .collect();
+    let mut reference = BruteForce::new();
+    let mut backends: Vec<Box<dyn Broadphase>> = vec![
+        Box::new(SpatialHash::new(2.0)),
+        Box::new(SweepAndPrune::new()),
+        Box::new(AabbTree::default()),
+    ];
+    let area = Aabb::new(Vector2::new(10.0, 10.0), Vector2::new(20.0, 15.0));
+
+    for step in 0..50 {
+        for (_, aabb) in &mut proxies {
+            if random(&mut seed) < 0.3 {
+                let offset = Vector2::new(random(&mut seed) - 0.5, random(&mut seed) - 0.5);
+
+                aabb.min += offset;
+                aabb.max += offset;
+            }
+        }
+
+        if step % 10 == 0 {
+            proxies.retain(|(i, _)| i % 7 != step % 7);
+        }
+
+        reference.update(&proxies);
+
+        let pairs = sorted(reference.pairs());
+        let mut found = reference.query(&area);
+
+        found.sort_unstable();
+
+        for backend in &mut backends {
+            backend.update(&proxies);
+
+            let mut found2 = backend.query(&area);
+
+            found2.sort_unstable();
+
+            assert_eq!(sorted(backend.pairs()), pairs);
+            assert_eq!(found2, found);
+        }
+    }
+}
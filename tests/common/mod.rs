@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use hex::{components::Trans, nalgebra::Vector2, parking_lot::RwLock, world::World, Id};
+use hex_physics::{
+    components::{BodyType, Collider, RigidBody},
+    systems::PhysicsManager,
+};
+use std::sync::Arc;
+
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+pub fn world() -> Arc<RwLock<World>> {
+    World::new()
+}
+
+pub fn manager(gravity: Vector2<f32>) -> PhysicsManager {
+    PhysicsManager::new(gravity, TIMESTEP, 8)
+}
+
+pub fn rect(width: f32, height: f32, body_type: BodyType) -> Arc<RwLock<Collider>> {
+    Collider::rect(
+        Vector2::new(width, height),
+        vec![0],
+        Vec::new(),
+        body_type,
+        false,
+    )
+}
+
+pub fn spawn(world: &World, position: Vector2<f32>, collider: Arc<RwLock<Collider>>) -> Id {
+    let mut em = world.em.write();
+    let e = em.add();
+
+    em.add_component(e, Trans::new(position, 0.0, Vector2::new(1.0, 1.0), true));
+    em.add_component(e, collider);
+
+    e
+}
+
+// Bodies get no inertia so contacts can't spin them.
+pub fn spawn_body(
+    world: &World,
+    position: Vector2<f32>,
+    collider: Arc<RwLock<Collider>>,
+    mass: f32,
+) -> Id {
+    let e = spawn(world, position, collider);
+
+    world
+        .em
+        .write()
+        .add_component(e, RigidBody::new(mass, 0.0, 0.0, 0.0));
+
+    e
+}
+
+pub fn position(world: &World, e: Id) -> Vector2<f32> {
+    world
+        .em
+        .read()
+        .get_component::<Trans>(e)
+        .unwrap()
+        .read()
+        .position()
+}
+
+pub fn rigid_body(world: &World, e: Id) -> Arc<RwLock<RigidBody>> {
+    world.em.read().get_component::<RigidBody>(e).unwrap()
+}
+
+pub fn velocity(world: &World, e: Id) -> Vector2<f32> {
+    rigid_body(world, e).read().velocity
+}
+
+pub fn step(manager: &mut PhysicsManager, world: &World, steps: usize) {
+    for _ in 0..steps {
+        manager.step(world);
+    }
+}
@@ -0,0 +1,122 @@
+use hex::{components::Trans, nalgebra::Vector2};
+use hex_physics::components::{BodyType, Material, RigidBody};
+
+fn approx(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+    (a - b).magnitude() < 1e-5
+}
+
+#[test]
+fn dynamic_velocity_integrates_force_and_gravity() {
+    let body = RigidBody::new(2.0, 1.0, 0.0, 0.0);
+    let body = &mut *body.write();
+
+    body.apply_force(Vector2::new(4.0, 0.0));
+    body.apply_torque(3.0);
+    body.integrate_velocity(BodyType::Dynamic, Vector2::new(0.0, -10.0), 0.5);
+
+    assert!(approx(body.velocity, Vector2::new(1.0, -5.0)));
+    assert!((body.angular_velocity - 1.5).abs() < 1e-5);
+    assert_eq!(body.force, Vector2::zeros());
+    assert_eq!(body.torque, 0.0);
+}
+
+#[test]
+fn damping_slows_dynamic_bodies() {
+    let body = RigidBody::new(1.0, 1.0, 1.0, 1.0);
+    let body = &mut *body.write();
+
+    body.velocity = Vector2::new(3.0, 0.0);
+    body.angular_velocity = 3.0;
+    body.integrate_velocity(BodyType::Dynamic, Vector2::zeros(), 0.5);
+
+    assert!(approx(body.velocity, Vector2::new(2.0, 0.0)));
+    assert!((body.angular_velocity - 2.0).abs() < 1e-5);
+}
+
+#[test]
+fn infinite_mass_ignores_gravity() {
+    let body = RigidBody::new(f32::INFINITY, 0.0, 0.0, 0.0);
+    let body = &mut *body.write();
+
+    body.apply_force(Vector2::new(1.0, 0.0));
+    body.integrate_velocity(BodyType::Dynamic, Vector2::new(0.0, -10.0), 1.0);
+
+    assert_eq!(body.velocity, Vector2::zeros());
+    assert_eq!(body.inverse_mass(), 0.0);
+}
+
+#[test]
+fn kinematic_velocity_ignores_forces() {
+    let body = RigidBody::new(1.0, 1.0, 1.0, 1.0);
+    let body = &mut *body.write();
+
+    body.velocity = Vector2::new(1.0, 2.0);
+    body.apply_force(Vector2::new(5.0, 5.0));
+    body.apply_torque(5.0);
+    body.integrate_velocity(BodyType::Kinematic, Vector2::new(0.0, -10.0), 1.0);
+
+    assert_eq!(body.velocity, Vector2::new(1.0, 2.0));
+    assert_eq!(body.angular_velocity, 0.0);
+    assert_eq!(body.force, Vector2::zeros());
+    assert_eq!(body.torque, 0.0);
+}
+
+#[test]
+fn position_integration_by_body_type() {
+    for (body_type, moves) in [
+        (BodyType::Dynamic, true),
+        (BodyType::Kinematic, true),
+        (BodyType::Static, false),
+    ] {
+        let body = RigidBody::new(1.0, 1.0, 0.0, 0.0);
+        let body = &mut *body.write();
+        let transform = Trans::new(Vector2::new(1.0, 1.0), 0.0, Vector2::new(1.0, 1.0), true);
+        let transform = &mut *transform.write();
+
+        body.velocity = Vector2::new(2.0, 0.0);
+        body.angular_velocity = 1.0;
+        body.integrate_position(transform, body_type, 0.5);
+
+        if moves {
+            assert!(approx(transform.position(), Vector2::new(2.0, 1.0)));
+            assert!((transform.rotation() - 0.5).abs() < 1e-5);
+            assert_eq!(body.previous, Some((Vector2::new(1.0, 1.0), 0.0)));
+        } else {
+            assert_eq!(transform.position(), Vector2::new(1.0, 1.0));
+            assert_eq!(transform.rotation(), 0.0);
+            assert_eq!(body.previous, None);
+        }
+    }
+}
+
+#[test]
+fn interpolation_blends_previous_pose() {
+    let body = RigidBody::new(1.0, 1.0, 0.0, 0.0);
+    let body = &mut *body.write();
+    let transform = Trans::new(Vector2::zeros(), 0.0, Vector2::new(1.0, 1.0), true);
+    let transform = &mut *transform.write();
+
+    assert_eq!(body.interpolated(transform, 0.5), (Vector2::zeros(), 0.0));
+
+    body.velocity = Vector2::new(2.0, 0.0);
+    body.angular_velocity = 1.0;
+    body.integrate_position(transform, BodyType::Dynamic, 1.0);
+
+    let (position, rotation) = body.interpolated(transform, 0.25);
+
+    assert!(approx(position, Vector2::new(0.5, 0.0)));
+    assert!((rotation - 0.25).abs() < 1e-5);
+}
+
+#[test]
+fn materials_combine() {
+    let bouncy = Material::new(0.8, 0.9);
+    let slick = Material::new(0.1, 0.1);
+    let combined = bouncy.combine(&slick);
+
+    assert_eq!(combined, slick.combine(&bouncy));
+    assert_eq!(combined.restitution, 0.8);
+    assert!((combined.friction - 0.3).abs() < 1e-6);
+    assert_eq!(bouncy.combine(&Material::new(0.0, 0.0)).friction, 0.0);
+    assert_eq!(Material::default(), Material::new(0.0, 0.5));
+}
@@ -0,0 +1,176 @@
+use hex::nalgebra::{Matrix3, Vector2};
+use hex_physics::{
+    components::{BodyType, Collider},
+    contact::Narrowphase,
+    shape::{Child, Shape},
+};
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+fn at(x: f32, y: f32, rotation: f32) -> Matrix3<f32> {
+    Matrix3::new_translation(&Vector2::new(x, y)) * Matrix3::new_rotation(rotation)
+}
+
+fn approx(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+    (a - b).magnitude() < 1e-3
+}
+
+fn collider(shape: Shape) -> Collider {
+    Collider::new(shape, Vec::new(), Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone()
+}
+
+fn square() -> Collider {
+    collider(Shape::Polygon(vec![
+        Vector2::new(-1.0, -1.0),
+        Vector2::new(-1.0, 1.0),
+        Vector2::new(1.0, 1.0),
+        Vector2::new(1.0, -1.0),
+    ]))
+}
+
+#[test]
+fn separated_distance() {
+    let closest = square()
+        .distance_at(&at(0.0, 0.0, 0.0), &at(3.0, 0.5, 0.0), &square())
+        .unwrap();
+
+    assert!((closest.distance - 1.0).abs() < 1e-4);
+    assert!(approx(closest.normal, Vector2::new(1.0, 0.0)));
+    assert!((closest.point.x - 1.0).abs() < 1e-4);
+    assert!((closest.other_point.x - 2.0).abs() < 1e-4);
+    assert!((closest.point.y - closest.other_point.y).abs() < 1e-4);
+
+    let closest = square()
+        .distance_at(&at(0.0, 0.0, FRAC_PI_4), &at(4.0, 0.0, 0.0), &square())
+        .unwrap();
+
+    assert!((closest.distance - (3.0 - SQRT_2)).abs() < 1e-4);
+    assert!(approx(closest.point, Vector2::new(SQRT_2, 0.0)));
+
+    let circle = collider(Shape::circle(1.0));
+    let closest = circle
+        .distance_at(
+            &at(0.0, 0.0, 0.0),
+            &at(3.0, 4.0, 0.0),
+            &collider(Shape::circle(1.5)),
+        )
+        .unwrap();
+
+    assert!((closest.distance - 2.5).abs() < 1e-4);
+    assert!(approx(closest.point, Vector2::new(0.6, 0.8)));
+    assert!(approx(closest.other_point, Vector2::new(2.1, 2.8)));
+}
+
+#[test]
+fn penetration_depth() {
+    let closest = square()
+        .distance_at(&at(0.0, 0.0, 0.0), &at(1.5, 0.2, 0.0), &square())
+        .unwrap();
+
+    assert!((closest.distance + 0.5).abs() < 1e-4);
+    assert!(approx(closest.normal, Vector2::new(1.0, 0.0)));
+
+    let capsule = collider(Shape::capsule(4.0, 0.5));
+    let closest = capsule
+        .distance_at(&at(0.0, 0.0, 0.0), &at(1.2, 0.0, 0.0), &square())
+        .unwrap();
+
+    assert!((closest.distance + 0.3).abs() < 1e-4);
+    assert!(approx(closest.normal, Vector2::new(1.0, 0.0)));
+}
+
+#[test]
+fn compound_distance_uses_closest_child() {
+    let compound = collider(Shape::Compound(vec![
+        Child::new(Shape::circle(0.5), Vector2::new(-3.0, 0.0), 0.0),
+        Child::new(Shape::circle(0.5), Vector2::new(3.0, 0.0), 0.0),
+    ]));
+    let closest = compound
+        .distance_at(&at(0.0, 0.0, 0.0), &at(6.0, 0.0, 0.0), &square())
+        .unwrap();
+
+    assert_eq!(closest.child, 1);
+    assert!((closest.distance - 1.5).abs() < 1e-4);
+}
+
+#[test]
+fn gjk_backend_agrees_with_sat() {
+    let shapes = [
+        square(),
+        collider(Shape::circle(0.7)),
+        collider(Shape::capsule(3.0, 0.4)),
+        collider(Shape::Polygon(vec![
+            Vector2::new(0.0, 1.0),
+            Vector2::new(-1.0, -0.5),
+            Vector2::new(1.0, -0.5),
+        ])),
+    ];
+    let placements = [
+        at(1.2, 0.3, 0.2),
+        at(-0.8, 1.1, 1.0),
+        at(0.5, -1.4, -0.4),
+        at(1.9, 1.9, FRAC_PI_4),
+        at(4.0, 0.0, 0.0),
+    ];
+
+    for a in &shapes {
+        for b in &shapes {
+            for placement in &placements {
+                let sat = a.contact_at(&at(0.0, 0.0, 0.0), placement, b);
+                let gjk = a
+                    .contacts_with(&at(0.0, 0.0, 0.0), placement, b, Narrowphase::Gjk)
+                    .into_iter()
+                    .next();
+
+                assert_eq!(sat.is_some(), gjk.is_some());
+
+                if let (Some(sat), Some(gjk)) = (sat, gjk) {
+                    assert!((sat.depth - gjk.depth).abs() < 1e-3);
+                    assert!(approx(sat.normal, gjk.normal));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn degenerate_overlaps_fall_back_to_sat() {
+    let circle = collider(Shape::circle(1.0));
+    let closest = circle
+        .distance_at(
+            &at(2.0, 2.0, 0.0),
+            &at(2.0, 2.0, 0.0),
+            &collider(Shape::circle(0.5)),
+        )
+        .unwrap();
+
+    assert!((closest.distance + 1.5).abs() < 1e-4);
+    assert!((closest.normal.magnitude() - 1.0).abs() < 1e-4);
+    assert!(((closest.point - Vector2::new(2.0, 2.0)).magnitude() - 1.0).abs() < 1e-4);
+    assert!(((closest.other_point - Vector2::new(2.0, 2.0)).magnitude() - 0.5).abs() < 1e-4);
+
+    let capsule = collider(Shape::capsule(4.0, 0.5));
+    let closest = circle
+        .distance_at(&at(0.0, 0.5, 0.0), &at(0.0, 0.0, 0.0), &capsule)
+        .unwrap();
+
+    assert!((closest.distance + 1.5).abs() < 1e-4);
+    assert!((closest.normal.x.abs() - 1.0).abs() < 1e-4);
+    assert!((closest.other_point.x.abs() - 0.5).abs() < 1e-4);
+    assert!(approx(
+        closest.point,
+        closest.other_point - closest.normal * closest.distance
+    ));
+
+    for (a, b) in [(&circle, &circle), (&circle, &capsule)] {
+        let manifold = a
+            .contacts_with(&at(0.0, 0.5, 0.0), &at(0.0, 0.5, 0.0), b, Narrowphase::Gjk)
+            .into_iter()
+            .next()
+            .unwrap();
+
+        assert!(manifold.depth > 1.0);
+        assert!(manifold.normal.x.is_finite() && manifold.normal.y.is_finite());
+    }
+}
@@ -0,0 +1,461 @@
+use hex::nalgebra::{Matrix3, Vector2};
+use hex_physics::{
+    components::{BodyType, Collider},
+    shape::{Child, Shape},
+};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+fn square() -> Collider {
+    Collider::rect(
+        Vector2::new(2.0, 2.0),
+        Vec::new(),
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    )
+    .read()
+    .clone()
+}
+
+fn at(x: f32, y: f32, rotation: f32) -> Matrix3<f32> {
+    Matrix3::new_translation(&Vector2::new(x, y)) * Matrix3::new_rotation(rotation)
+}
+
+fn approx(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+    (a - b).magnitude() < 1e-4
+}
+
+#[test]
+fn overlapping_squares() {
+    let mtv = square()
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(1.5, 0.0, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(mtv, Vector2::new(0.5, 0.0)));
+}
+
+#[test]
+fn separated_squares() {
+    assert!(square()
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(2.5, 0.0, 0.0), &square())
+        .is_none());
+    assert!(square()
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(1.0, -2.1, 0.0), &square())
+        .is_none());
+}
+
+#[test]
+fn normal_points_toward_other() {
+    let a = square();
+    let b = square();
+    let left = a
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(-1.8, 0.2, 0.0), &b)
+        .unwrap();
+    let below = a
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(0.3, -1.6, 0.0), &b)
+        .unwrap();
+
+    assert!(approx(left, Vector2::new(-0.2, 0.0)));
+    assert!(approx(below, Vector2::new(0.0, -0.4)));
+}
+
+#[test]
+fn symmetric_mtv() {
+    let a = square();
+    let b = square();
+    let ab = a
+        .intersecting_at(&at(0.0, 0.0, 0.3), &at(1.2, 0.9, -0.2), &b)
+        .unwrap();
+    let ba = b
+        .intersecting_at(&at(1.2, 0.9, -0.2), &at(0.0, 0.0, 0.3), &a)
+        .unwrap();
+
+    assert!(approx(ab, -ba));
+}
+
+#[test]
+fn rotated_rect_separated_on_other_axis() {
+    // Overlaps on both of the axis-aligned square's axes, but the diamond's own
+    // edge normals separate the pair.
+    let a = square();
+    let b = square();
+
+    assert!(a
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(2.3, 2.3, FRAC_PI_4), &b)
+        .is_none());
+    assert!(b
+        .intersecting_at(&at(2.3, 2.3, FRAC_PI_4), &at(0.0, 0.0, 0.0), &a)
+        .is_none());
+}
+
+#[test]
+fn rotated_rect_overlapping() {
+    let a = square();
+    let b = square();
+    let mtv = a
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(2.2, 0.0, FRAC_PI_4), &b)
+        .unwrap();
+
+    assert!(approx(mtv, Vector2::new(2.0f32.sqrt() - 1.2, 0.0)));
+}
+
+#[test]
+fn triangle_and_square() {
+    let triangle = Collider::new(
+        Shape::Polygon(vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(2.0, 0.0),
+            Vector2::new(0.0, 2.0),
+        ]),
+        Vec::new(),
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    )
+    .read()
+    .clone();
+
+    assert!(triangle
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(1.2, 1.2, 0.0), &square())
+        .is_some());
+    assert!(triangle
+        .intersecting_at(&at(0.0, 0.0, 0.0), &at(2.1, 2.1, 0.0), &square())
+        .is_none());
+}
+
+#[test]
+fn resting_face_contact_has_two_points() {
+    let a = square();
+    let b = square();
+    let manifold = a
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.5, 1.9, 0.0), &b)
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 2);
+    assert!(manifold
+        .points
+        .iter()
+        .all(|p| p.x >= -0.5 - 1e-4 && p.x <= 1.0 + 1e-4));
+}
+
+#[test]
+fn corner_contact_has_one_point() {
+    let a = square();
+    let b = square();
+    let manifold = a
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, 2.3, FRAC_PI_4), &b)
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert_eq!(manifold.points.len(), 1);
+    assert!(approx(
+        manifold.points[0],
+        Vector2::new(0.0, 2.3 - 2.0f32.sqrt())
+    ));
+}
+
+fn circle(radius: f32) -> Collider {
+    Collider::circle(radius, Vec::new(), Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone()
+}
+
+#[test]
+fn overlapping_circles() {
+    let manifold = circle(1.0)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(1.2, 1.6, 0.0), &circle(1.5))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.6, 0.8)));
+    assert!((manifold.depth - 0.5).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 1);
+}
+
+#[test]
+fn separated_circles() {
+    assert!(circle(1.0)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(2.1, 0.0, 0.0), &circle(1.0))
+        .is_none());
+}
+
+#[test]
+fn circle_on_polygon_face() {
+    let manifold = square()
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.3, 1.4, 0.0), &circle(0.5))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert!(approx(manifold.points[0], Vector2::new(0.3, 0.9)));
+}
+
+#[test]
+fn circle_near_polygon_corner() {
+    // Inside the square's bounds on both axes, but past the rounded corner.
+    assert!(circle(0.5)
+        .contact_at(&at(1.4, 1.4, 0.0), &at(0.0, 0.0, 0.0), &square())
+        .is_none());
+
+    let manifold = circle(0.5)
+        .contact_at(&at(1.3, 1.3, 0.0), &at(0.0, 0.0, 0.0), &square())
+        .unwrap();
+    let diagonal = Vector2::new(-1.0, -1.0).normalize();
+
+    assert!(approx(manifold.normal, diagonal));
+    assert!((manifold.depth - (0.5 - 0.3 * 2.0f32.sqrt())).abs() < 1e-4);
+}
+
+#[test]
+fn circle_inside_polygon() {
+    let manifold = square()
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.8, 0.1, 0.0), &circle(0.25))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.depth - 0.45).abs() < 1e-4);
+}
+
+fn capsule(height: f32, radius: f32) -> Collider {
+    Collider::capsule(
+        height,
+        radius,
+        Vec::new(),
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    )
+    .read()
+    .clone()
+}
+
+#[test]
+fn capsule_standing_on_polygon() {
+    let manifold = square()
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.2, 1.9, 0.0), &capsule(2.0, 0.5))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 1);
+    assert!(approx(manifold.points[0], Vector2::new(0.2, 0.9)));
+}
+
+#[test]
+fn capsule_lying_on_polygon() {
+    let manifold = square()
+        .contact_at(
+            &at(0.0, 0.0, 0.0),
+            &at(0.0, 1.4, FRAC_PI_2),
+            &capsule(2.0, 0.5),
+        )
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 2);
+}
+
+#[test]
+fn capsule_against_circle_and_capsule() {
+    let manifold = capsule(2.0, 0.5)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.8, 0.3, 0.0), &circle(0.5))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.depth - 0.2).abs() < 1e-4);
+
+    let manifold = capsule(2.0, 0.5)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.9, 0.5, 0.0), &capsule(2.0, 0.5))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 2);
+}
+
+#[test]
+fn collinear_capsules_separated() {
+    assert!(capsule(2.0, 0.5)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, 2.2, 0.0), &capsule(2.0, 0.5))
+        .is_none());
+    assert!(capsule(2.0, 0.5)
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, 1.8, 0.0), &capsule(2.0, 0.5))
+        .is_some());
+}
+
+fn dumbbell() -> Collider {
+    let mut end = Child::new(Shape::circle(0.5), Vector2::new(1.5, 0.0), 0.0);
+
+    end.layers = Some(vec![1]);
+
+    Collider::compound(
+        vec![
+            Child::new(Shape::circle(0.5), Vector2::new(-1.5, 0.0), 0.0),
+            Child::new(Shape::capsule(3.0, 0.2), Vector2::zeros(), FRAC_PI_2),
+            end,
+        ],
+        vec![0],
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    )
+    .read()
+    .clone()
+}
+
+#[test]
+fn compound_reports_child() {
+    let dumbbell = dumbbell();
+    let manifold = dumbbell
+        .contact_at(&at(0.0, 0.0, 0.0), &at(2.3, 0.0, 0.0), &circle(0.5))
+        .unwrap();
+
+    assert_eq!(manifold.child, 2);
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.depth - 0.2).abs() < 1e-4);
+
+    let manifold = circle(0.5)
+        .contact_at(&at(-2.3, 0.0, 0.0), &at(0.0, 0.0, 0.0), &dumbbell)
+        .unwrap();
+
+    assert_eq!(manifold.other_child, 0);
+    assert!(dumbbell
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, 1.0, 0.0), &circle(0.5))
+        .is_none());
+}
+
+#[test]
+fn compound_child_overrides() {
+    let dumbbell = dumbbell();
+    let other = Collider::circle(0.5, vec![1], Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone();
+
+    assert_eq!(dumbbell.layers_of(0), &[0]);
+    assert_eq!(dumbbell.layers_of(2), &[1]);
+    assert!(!dumbbell.interacts(0, &other, 0));
+    assert!(dumbbell.interacts(2, &other, 0));
+    assert!((dumbbell.shape.radius() - 2.0).abs() < 1e-4);
+
+    let aabb = dumbbell.aabb_at(&at(0.0, 0.0, 0.0)).unwrap();
+
+    assert!(approx(aabb.min, Vector2::new(-2.0, -0.5)));
+    assert!(approx(aabb.max, Vector2::new(2.0, 0.5)));
+}
+
+fn ground(shape: Shape) -> Collider {
+    Collider::new(shape, Vec::new(), Vec::new(), BodyType::Static, false)
+        .read()
+        .clone()
+}
+
+#[test]
+fn edge_is_one_sided() {
+    let edge = ground(Shape::edge(Vector2::new(-4.0, 0.0), Vector2::new(4.0, 0.0)));
+    let manifold = edge
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, 0.9, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+    assert_eq!(manifold.points.len(), 2);
+
+    let manifold = square()
+        .contact_at(&at(0.0, 0.9, 0.0), &at(0.0, 0.0, 0.0), &edge)
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, -1.0)));
+    assert!(edge
+        .contact_at(&at(0.0, 0.0, 0.0), &at(0.0, -0.9, 0.0), &square())
+        .is_none());
+}
+
+#[test]
+fn chain_seams_are_smooth() {
+    let chain = ground(Shape::chain(
+        vec![
+            Vector2::new(-4.0, 0.0),
+            Vector2::new(0.0, 0.0),
+            Vector2::new(4.0, 0.0),
+        ],
+        false,
+    ));
+    let lone = ground(Shape::edge(Vector2::new(0.0, 0.0), Vector2::new(4.0, 0.0)));
+    let manifold = lone
+        .contact_at(&at(0.0, 0.0, 0.0), &at(-0.95, 0.9, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(-1.0, 0.0)));
+
+    for x in [-0.95, -0.5, 0.0, 0.5, 0.95] {
+        let manifolds = chain.contacts_at(&at(0.0, 0.0, 0.0), &at(x, 0.9, 0.0), &square());
+
+        assert!(!manifolds.is_empty());
+        assert!(manifolds
+            .iter()
+            .all(|m| approx(m.normal, Vector2::new(0.0, 1.0)) && (m.depth - 0.1).abs() < 1e-4));
+    }
+
+    let manifold = chain
+        .contact_at(&at(0.0, 0.0, 0.0), &at(3.0, 0.3, 0.0), &circle(0.5))
+        .unwrap();
+
+    assert_eq!(manifold.child, 1);
+}
+
+fn scaled(x: f32, y: f32, rotation: f32, scale: Vector2<f32>) -> Matrix3<f32> {
+    at(x, y, rotation) * Matrix3::new_nonuniform_scaling(&scale)
+}
+
+#[test]
+fn scaled_and_rotated_polygons() {
+    let wide = scaled(0.0, 0.0, 0.0, Vector2::new(2.0, 1.0));
+    let manifold = square()
+        .contact_at(&wide, &at(2.5, 0.0, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.depth - 0.5).abs() < 1e-4);
+
+    let tall = scaled(0.0, 0.0, FRAC_PI_2, Vector2::new(2.0, 1.0));
+    let manifold = square()
+        .contact_at(&tall, &at(0.0, 2.5, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.5).abs() < 1e-4);
+    assert!(square()
+        .contact_at(&tall, &at(2.1, 0.0, 0.0), &square())
+        .is_none());
+
+    let aabb = square().aabb_at(&tall).unwrap();
+
+    assert!(approx(aabb.min, Vector2::new(-1.0, -2.0)));
+    assert!(approx(aabb.max, Vector2::new(1.0, 2.0)));
+}
+
+#[test]
+fn scaled_circles_stay_round() {
+    let big = scaled(0.0, 0.0, FRAC_PI_4, Vector2::new(2.0, 1.0));
+    let manifold = circle(1.0)
+        .contact_at(&big, &at(0.0, 2.5, 0.0), &circle(1.0))
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.5).abs() < 1e-4);
+    assert!((circle(1.0).boundary_at(&big) - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn parented_matrices_compose() {
+    let parent = scaled(3.0, 0.0, FRAC_PI_2, Vector2::new(2.0, 2.0));
+    let child = at(1.0, 0.0, 0.0);
+    let manifold = square()
+        .contact_at(&(parent * child), &at(3.0, 4.5, 0.0), &square())
+        .unwrap();
+
+    assert!(approx(manifold.normal, Vector2::new(0.0, 1.0)));
+    assert!((manifold.depth - 0.5).abs() < 1e-4);
+}
@@ -0,0 +1,625 @@
+mod common;
+
+use common::*;
+use hex::{components::Trans, nalgebra::Vector2, parking_lot::RwLock};
+use hex_physics::{
+    components::{BodyType, Collider, Parent},
+    event::CollisionState,
+    query::QueryFilter,
+    shape::{Child, Shape},
+};
+use std::sync::Arc;
+
+#[test]
+fn gravity_accelerates_bodies() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let e = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    step(&mut manager, world, 1);
+
+    let v = -10.0 * TIMESTEP;
+
+    assert!((velocity(world, e) - Vector2::new(0.0, v)).magnitude() < 1e-5);
+    assert!((position(world, e) - Vector2::new(0.0, v * TIMESTEP)).magnitude() < 1e-6);
+
+    step(&mut manager, world, 59);
+
+    assert!((velocity(world, e).y + 10.0).abs() < 1e-3);
+}
+
+#[test]
+fn forces_last_one_step() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let e = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        2.0,
+    );
+
+    rigid_body(world, e)
+        .write()
+        .apply_force(Vector2::new(120.0, 0.0));
+    step(&mut manager, world, 1);
+
+    assert!((velocity(world, e) - Vector2::new(1.0, 0.0)).magnitude() < 1e-5);
+
+    step(&mut manager, world, 1);
+
+    assert!((velocity(world, e) - Vector2::new(1.0, 0.0)).magnitude() < 1e-5);
+    assert!((position(world, e) - Vector2::new(2.0 * TIMESTEP, 0.0)).magnitude() < 1e-5);
+}
+
+#[test]
+fn box_comes_to_rest_on_ground() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let ground = spawn(world, Vector2::zeros(), rect(20.0, 1.0, BodyType::Static));
+    let e = spawn_body(
+        world,
+        Vector2::new(0.0, 3.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    step(&mut manager, world, 300);
+
+    assert!((position(world, e).y - 1.0).abs() < 0.05);
+    assert!(velocity(world, e).magnitude() < 0.1);
+    assert_eq!(position(world, ground), Vector2::zeros());
+}
+
+#[test]
+fn advance_runs_fixed_steps() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+
+    assert_eq!(manager.advance(world, TIMESTEP * 0.5), 0);
+    assert!((*manager.alpha.read() - 0.5).abs() < 1e-4);
+    assert_eq!(manager.advance(world, TIMESTEP * 2.0), 2);
+    assert!((*manager.alpha.read() - 0.5).abs() < 1e-4);
+    assert_eq!(manager.advance(world, 1.0), 8);
+    assert!(*manager.alpha.read() < 1.0);
+}
+
+#[test]
+fn invalid_timesteps_never_poison_the_accumulator() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let e = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    for timestep in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        manager.timestep = timestep;
+
+        assert_eq!(manager.advance(world, 1.0), 0);
+    }
+
+    manager.timestep = TIMESTEP;
+
+    assert_eq!(manager.advance(world, f32::NAN), 0);
+    assert_eq!(manager.advance(world, -1.0), 0);
+    assert_eq!(manager.advance(world, TIMESTEP * 1.5), 1);
+    assert!(manager.alpha.read().is_finite());
+    assert!(velocity(world, e).y < 0.0);
+}
+
+#[test]
+fn events_queue_until_drained() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+
+    spawn(world, Vector2::zeros(), rect(1.0, 1.0, BodyType::Static));
+    spawn_body(
+        world,
+        Vector2::new(0.0, 0.9),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+    step(&mut manager, world, 2);
+
+    assert_eq!(manager.events.read().len(), 2);
+
+    manager.advance(world, TIMESTEP);
+
+    let states: Vec<_> = manager.drain_events().iter().map(|e| e.state).collect();
+
+    assert_eq!(
+        states,
+        vec![
+            CollisionState::Started,
+            CollisionState::Persisted,
+            CollisionState::Persisted
+        ]
+    );
+    assert!(manager.events.read().is_empty());
+}
+
+#[test]
+fn correction_is_split_by_inverse_mass() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let light = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+    let heavy = spawn_body(
+        world,
+        Vector2::new(0.8, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        3.0,
+    );
+
+    step(&mut manager, world, 1);
+
+    let moved = -position(world, light).x;
+    let moved2 = position(world, heavy).x - 0.8;
+    let expected = (0.2 - manager.slop) * manager.correction;
+
+    assert!(moved > 0.0 && moved2 > 0.0);
+    assert!((moved - 3.0 * moved2).abs() < 1e-5);
+    assert!((moved + moved2 - expected).abs() < 1e-5);
+    assert_eq!(position(world, light).y, 0.0);
+}
+
+#[test]
+fn overlap_within_slop_is_left_alone() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let e = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+    let e2 = spawn_body(
+        world,
+        Vector2::new(1.0 - manager.slop * 0.5, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    step(&mut manager, world, 1);
+
+    assert_eq!(position(world, e), Vector2::zeros());
+    assert_eq!(
+        position(world, e2),
+        Vector2::new(1.0 - manager.slop * 0.5, 0.0)
+    );
+    assert_eq!(manager.manifolds.read().len(), 1);
+}
+
+#[test]
+fn static_bodies_are_never_pushed() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let wall = spawn(world, Vector2::zeros(), rect(1.0, 1.0, BodyType::Static));
+    let e = spawn_body(
+        world,
+        Vector2::new(0.8, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    step(&mut manager, world, 1);
+
+    let expected = 0.8 + (0.2 - manager.slop) * manager.correction;
+
+    assert_eq!(position(world, wall), Vector2::zeros());
+    assert!((position(world, e).x - expected).abs() < 1e-5);
+}
+
+#[test]
+fn kinematic_bodies_push_without_being_pushed() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let paddle = spawn_body(
+        world,
+        Vector2::zeros(),
+        rect(1.0, 1.0, BodyType::Kinematic),
+        1.0,
+    );
+    let e = spawn_body(
+        world,
+        Vector2::new(1.0, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    rigid_body(world, paddle).write().velocity = Vector2::new(2.0, 0.0);
+    step(&mut manager, world, 30);
+
+    assert_eq!(velocity(world, paddle), Vector2::new(2.0, 0.0));
+    assert!((position(world, paddle) - Vector2::new(1.0, 0.0)).magnitude() < 1e-4);
+    assert!(position(world, e).x > position(world, paddle).x + 0.9);
+}
+
+#[test]
+fn static_pairs_are_skipped() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+
+    spawn(world, Vector2::zeros(), rect(1.0, 1.0, BodyType::Static));
+    spawn(
+        world,
+        Vector2::new(0.5, 0.0),
+        rect(1.0, 1.0, BodyType::Static),
+    );
+    step(&mut manager, world, 1);
+
+    assert!(manager.manifolds.read().is_empty());
+    assert!(manager.events.read().is_empty());
+}
+
+fn drop_on_ground(restitution: f32, speed: f32) -> Vector2<f32> {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let ground = rect(20.0, 1.0, BodyType::Static);
+    let ball = rect(1.0, 1.0, BodyType::Dynamic);
+
+    ground.write().material.restitution = restitution;
+    spawn(world, Vector2::zeros(), ground);
+
+    let e = spawn_body(world, Vector2::new(0.0, 0.995), ball, 1.0);
+
+    rigid_body(world, e).write().velocity = Vector2::new(0.0, -speed);
+    step(&mut manager, world, 1);
+
+    velocity(world, e)
+}
+
+#[test]
+fn restitution_bounces_fast_impacts() {
+    assert!((drop_on_ground(1.0, 5.0) - Vector2::new(0.0, 5.0)).magnitude() < 1e-4);
+    assert!((drop_on_ground(0.5, 5.0) - Vector2::new(0.0, 2.5)).magnitude() < 1e-4);
+    assert!(drop_on_ground(0.0, 5.0).magnitude() < 1e-4);
+    // Below the threshold contacts don't bounce at all.
+    assert!(drop_on_ground(1.0, 0.5).magnitude() < 1e-4);
+}
+
+fn slide(friction: f32) -> Vector2<f32> {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let ground = rect(20.0, 1.0, BodyType::Static);
+    let sled = rect(1.0, 1.0, BodyType::Dynamic);
+
+    ground.write().material.friction = friction;
+    sled.write().material.friction = friction;
+    spawn(world, Vector2::zeros(), ground);
+
+    let e = spawn_body(world, Vector2::new(0.0, 0.995), sled, 1.0);
+
+    rigid_body(world, e).write().velocity = Vector2::new(2.0, 0.0);
+    step(&mut manager, world, 12);
+
+    velocity(world, e)
+}
+
+#[test]
+fn friction_slows_sliding_bodies() {
+    let frictionless = slide(0.0);
+    let rough = slide(0.5);
+
+    assert!((frictionless.x - 2.0).abs() < 1e-4);
+    // Coulomb friction takes at most friction * g off per second.
+    assert!((rough.x - (2.0 - 0.5 * 10.0 * 12.0 * TIMESTEP)).abs() < 0.05);
+    assert!(slide(10.0).x.abs() < 1e-4);
+}
+
+fn block(width: f32, height: f32, offset: Vector2<f32>) -> Child {
+    let (x, y) = (width / 2.0, height / 2.0);
+
+    Child::new(
+        Shape::Polygon(vec![
+            Vector2::new(-x, -y),
+            Vector2::new(x, -y),
+            Vector2::new(x, y),
+            Vector2::new(-x, y),
+        ]),
+        offset,
+        0.0,
+    )
+}
+
+#[test]
+fn events_keep_one_order() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let ground = spawn(
+        world,
+        Vector2::zeros(),
+        Collider::compound(
+            vec![
+                block(2.0, 1.0, Vector2::new(-2.0, 0.0)),
+                block(2.0, 1.0, Vector2::new(2.0, 0.0)),
+            ],
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    let e = spawn_body(
+        world,
+        Vector2::new(2.0, 0.9),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    for state in [CollisionState::Started, CollisionState::Persisted] {
+        manager.drain_events();
+        manager.step(world);
+
+        let events = manager.events.read();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].state, state);
+        assert_eq!(
+            (
+                events[0].entity,
+                events[0].child,
+                events[0].other,
+                events[0].other_child
+            ),
+            (ground, 1, e, 0)
+        );
+        assert!(events[0].manifold.as_ref().unwrap().normal.y > 0.99);
+    }
+
+    world
+        .em
+        .read()
+        .get_component::<Trans>(e)
+        .unwrap()
+        .write()
+        .set_position(Vector2::new(2.0, 5.0));
+    manager.drain_events();
+    manager.step(world);
+
+    let events = manager.events.read();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].state, CollisionState::Ended);
+    assert_eq!(
+        (
+            events[0].entity,
+            events[0].child,
+            events[0].other,
+            events[0].other_child
+        ),
+        (ground, 1, e, 0)
+    );
+}
+
+#[test]
+fn nested_compounds_report_each_child_once() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let nested = Shape::Compound(vec![
+        block(1.0, 1.0, Vector2::new(-0.5, 0.0)),
+        block(1.0, 1.0, Vector2::new(0.5, 0.0)),
+    ]);
+
+    spawn(
+        world,
+        Vector2::zeros(),
+        Collider::compound(
+            vec![Child::new(nested, Vector2::zeros(), 0.0)],
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    spawn_body(
+        world,
+        Vector2::new(0.0, 0.9),
+        rect(2.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+    step(&mut manager, world, 1);
+
+    assert_eq!(manager.manifolds.read().len(), 2);
+    assert_eq!(manager.events.read().len(), 1);
+    assert_eq!(manager.events.read()[0].state, CollisionState::Started);
+}
+
+#[test]
+fn parented_colliders_are_never_moved() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let parent = spawn(world, Vector2::zeros(), rect(1.0, 1.0, BodyType::Dynamic));
+    let child = spawn(
+        world,
+        Vector2::new(2.0, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+    );
+    let e = spawn_body(
+        world,
+        Vector2::new(2.8, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    world.em.write().add_component(child, Parent::new(parent));
+    step(&mut manager, world, 1);
+
+    assert_eq!(position(world, parent), Vector2::zeros());
+    assert_eq!(position(world, child), Vector2::new(2.0, 0.0));
+    assert!(
+        (position(world, e).x - (2.8 + (0.2 - manager.slop) * manager.correction)).abs() < 1e-5
+    );
+}
+
+#[test]
+fn parented_bodies_are_not_simulated() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let parent = spawn(world, Vector2::zeros(), rect(1.0, 1.0, BodyType::Static));
+    let child = spawn_body(
+        world,
+        Vector2::new(2.0, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    world.em.write().add_component(child, Parent::new(parent));
+    rigid_body(world, child).write().velocity = Vector2::new(1.0, 0.0);
+    step(&mut manager, world, 10);
+
+    assert_eq!(position(world, child), Vector2::new(2.0, 0.0));
+    assert_eq!(velocity(world, child), Vector2::new(1.0, 0.0));
+}
+
+#[test]
+fn hierarchy_places_colliders_in_the_world() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let parent = {
+        let mut em = world.em.write();
+        let parent = em.add();
+
+        em.add_component(
+            parent,
+            Trans::new(
+                Vector2::new(10.0, 0.0),
+                std::f32::consts::FRAC_PI_2,
+                Vector2::new(2.0, 2.0),
+                true,
+            ),
+        );
+
+        parent
+    };
+    let child = spawn(
+        world,
+        Vector2::new(1.0, 0.0),
+        rect(1.0, 1.0, BodyType::Static),
+    );
+    let ball = spawn_body(
+        world,
+        Vector2::new(10.0, 4.0),
+        Collider::circle(0.5, vec![0], Vec::new(), BodyType::Dynamic, false),
+        1.0,
+    );
+
+    world.em.write().add_component(child, Parent::new(parent));
+    step(&mut manager, world, 240);
+
+    // The child sits at (10, 2) as a 2x2 square, so the ball rests at 3.5.
+    assert!((position(world, ball) - Vector2::new(10.0, 3.5)).magnitude() < 0.05);
+
+    let filter = QueryFilter::new(vec![0], Vec::new());
+
+    assert_eq!(
+        manager.overlap_point(world, Vector2::new(10.9, 1.1), &filter),
+        vec![child]
+    );
+    assert!(manager
+        .overlap_point(world, Vector2::new(11.5, 0.0), &filter)
+        .is_empty());
+}
+
+fn sensor(radius: f32) -> Arc<RwLock<Collider>> {
+    Collider::circle(radius, vec![0], Vec::new(), BodyType::Static, true)
+}
+
+#[test]
+fn sensors_report_overlaps_without_pushing() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let trigger = spawn(world, Vector2::zeros(), sensor(1.0));
+    let e = spawn_body(
+        world,
+        Vector2::new(-3.0, 0.0),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+
+    rigid_body(world, e).write().velocity = Vector2::new(6.0, 0.0);
+
+    let mut states = Vec::new();
+
+    for _ in 0..60 {
+        manager.drain_events();
+        manager.step(world);
+
+        for event in manager.events.read().iter() {
+            assert!(event.sensor);
+            assert_eq!((event.entity, event.other), (trigger, e));
+
+            if states.last() != Some(&event.state) {
+                states.push(event.state);
+            }
+        }
+    }
+
+    assert_eq!(
+        states,
+        vec![
+            CollisionState::Started,
+            CollisionState::Persisted,
+            CollisionState::Ended
+        ]
+    );
+    assert!(manager.manifolds.read().is_empty());
+    assert_eq!(velocity(world, e), Vector2::new(6.0, 0.0));
+    assert!((position(world, e).x - 3.0).abs() < 1e-3);
+}
+
+#[test]
+fn sensor_pairs_are_opt_in() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let moving = sensor(1.0);
+
+    moving.write().body_type = BodyType::Kinematic;
+    spawn(world, Vector2::zeros(), sensor(1.0));
+    spawn(world, Vector2::new(0.5, 0.0), moving);
+    step(&mut manager, world, 1);
+
+    assert!(manager.events.read().is_empty());
+
+    manager.sensor_vs_sensor = true;
+    step(&mut manager, world, 1);
+
+    let events = manager.events.read();
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].sensor);
+    assert_eq!(events[0].state, CollisionState::Started);
+    assert!(events[0].manifold.is_some());
+}
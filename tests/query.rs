@@ -0,0 +1,766 @@
+mod common;
+
+use common::{manager, spawn, world};
+use hex::{
+    components::Trans,
+    nalgebra::{Matrix3, Vector2},
+    Id,
+};
+use hex_physics::{
+    aabb::Aabb,
+    components::{BodyType, Collider, CollisionFilter},
+    contact::Narrowphase,
+    query::QueryFilter,
+    shape::{Child, Shape},
+};
+
+fn at(x: f32, y: f32, rotation: f32) -> Matrix3<f32> {
+    Matrix3::new_translation(&Vector2::new(x, y)) * Matrix3::new_rotation(rotation)
+}
+
+fn approx(a: Vector2<f32>, b: Vector2<f32>) -> bool {
+    (a - b).magnitude() < 1e-4
+}
+
+fn collider(shape: Shape, layers: Vec<Id>) -> Collider {
+    Collider::new(shape, layers, Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone()
+}
+
+fn square() -> Shape {
+    Shape::Polygon(vec![
+        Vector2::new(-1.0, -1.0),
+        Vector2::new(-1.0, 1.0),
+        Vector2::new(1.0, 1.0),
+        Vector2::new(1.0, -1.0),
+    ])
+}
+
+#[test]
+fn raycast_polygon() {
+    let square = collider(square(), Vec::new());
+    let right = Vector2::new(1.0, 0.0);
+    let (_, t, normal) = square
+        .raycast_at(&at(5.0, 0.5, 0.0), Vector2::zeros(), right, 10.0)
+        .unwrap();
+
+    assert!((t - 4.0).abs() < 1e-4);
+    assert!(approx(normal, Vector2::new(-1.0, 0.0)));
+    assert!(square
+        .raycast_at(&at(5.0, 0.5, 0.0), Vector2::zeros(), right, 3.5)
+        .is_none());
+    assert!(square
+        .raycast_at(&at(5.0, 1.5, 0.0), Vector2::zeros(), right, 10.0)
+        .is_none());
+    assert!(square
+        .raycast_at(&at(-5.0, 0.0, 0.0), Vector2::zeros(), right, 10.0)
+        .is_none());
+
+    let (_, t, normal) = square
+        .raycast_at(&at(0.0, 0.0, 0.0), Vector2::zeros(), right, 10.0)
+        .unwrap();
+
+    assert_eq!(t, 0.0);
+    assert!(approx(normal, -right));
+}
+
+#[test]
+fn raycast_rounded_shapes() {
+    let circle = collider(Shape::circle(1.0), Vec::new());
+    let (_, t, normal) = circle
+        .raycast_at(
+            &at(3.0, 3.0, 0.0),
+            Vector2::zeros(),
+            Vector2::new(1.0, 1.0).normalize(),
+            10.0,
+        )
+        .unwrap();
+
+    assert!((t - (18.0f32.sqrt() - 1.0)).abs() < 1e-4);
+    assert!(approx(normal, -Vector2::new(1.0, 1.0).normalize()));
+
+    let capsule = collider(Shape::capsule(4.0, 0.5), Vec::new());
+    let (_, t, normal) = capsule
+        .raycast_at(
+            &at(0.0, 0.0, 0.0),
+            Vector2::new(-3.0, 1.0),
+            Vector2::new(1.0, 0.0),
+            10.0,
+        )
+        .unwrap();
+
+    assert!((t - 2.5).abs() < 1e-4);
+    assert!(approx(normal, Vector2::new(-1.0, 0.0)));
+
+    let (_, t, _) = capsule
+        .raycast_at(
+            &at(0.0, 0.0, 0.0),
+            Vector2::new(0.0, 5.0),
+            Vector2::new(0.0, -1.0),
+            10.0,
+        )
+        .unwrap();
+
+    assert!((t - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn raycast_one_sided_edge_and_children() {
+    let edge = collider(
+        Shape::edge(Vector2::new(-1.0, 0.0), Vector2::new(1.0, 0.0)),
+        Vec::new(),
+    );
+    let down = Vector2::new(0.0, -1.0);
+
+    let (_, t, normal) = edge
+        .raycast_at(&at(0.0, 0.0, 0.0), Vector2::new(0.0, 2.0), down, 10.0)
+        .unwrap();
+
+    assert!((t - 2.0).abs() < 1e-4);
+    assert!(approx(normal, Vector2::new(0.0, 1.0)));
+    assert!(edge
+        .raycast_at(&at(0.0, 0.0, 0.0), Vector2::new(0.0, -2.0), -down, 10.0)
+        .is_none());
+
+    let compound = collider(
+        Shape::Compound(vec![
+            Child::new(Shape::circle(0.5), Vector2::new(0.0, 0.0), 0.0),
+            Child::new(square(), Vector2::new(4.0, 0.0), 0.0),
+        ]),
+        Vec::new(),
+    );
+    let (child, t, _) = compound
+        .raycast_at(
+            &at(0.0, 0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(-1.0, 0.0),
+            20.0,
+        )
+        .unwrap();
+
+    assert_eq!(child, 1);
+    assert!((t - 5.0).abs() < 1e-4);
+}
+
+#[test]
+fn filter_follows_layer_rule() {
+    let mut pieces = Child::new(square(), Vector2::zeros(), 0.0);
+
+    pieces.layers = Some(vec![2]);
+
+    let mut c = collider(
+        Shape::Compound(vec![Child::new(square(), Vector2::zeros(), 0.0), pieces]),
+        vec![1],
+    );
+    let filter = QueryFilter::new(vec![1], Vec::new());
+
+    assert!(filter.matches(0, &c, 0));
+    assert!(!filter.matches(0, &c, 1));
+    assert!(QueryFilter::new(vec![1, 2], vec![2]).matches(0, &c, 0));
+    assert!(!QueryFilter::new(vec![1, 2], vec![2]).matches(0, &c, 1));
+    assert!(!QueryFilter {
+        exclude: vec![0],
+        ..filter.clone()
+    }
+    .matches(0, &c, 0));
+
+    c.ignore = vec![1];
+
+    assert!(!filter.matches(0, &c, 0));
+}
+
+#[test]
+fn shape_cast_finds_first_contact() {
+    let square = collider(square(), Vec::new());
+    let (toi, manifold) = square
+        .cast_at(
+            &at(0.0, 0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            &at(5.0, 0.5, 0.0),
+            &square,
+        )
+        .unwrap();
+
+    assert!((toi - 0.3).abs() < 1e-3);
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!((manifold.points[0].x - 4.0).abs() < 1e-3);
+
+    let wall = collider(
+        Shape::Polygon(vec![
+            Vector2::new(-0.05, -5.0),
+            Vector2::new(-0.05, 5.0),
+            Vector2::new(0.05, 5.0),
+            Vector2::new(0.05, -5.0),
+        ]),
+        Vec::new(),
+    );
+    let ball = collider(Shape::circle(0.5), Vec::new());
+    let (toi, manifold) = ball
+        .cast_at(
+            &at(-10.0, 1.0, 0.0),
+            Vector2::new(20.0, 0.0),
+            &at(0.0, 0.0, 0.0),
+            &wall,
+        )
+        .unwrap();
+
+    assert!((toi - 9.45 / 20.0).abs() < 1e-3);
+    assert!(approx(manifold.normal, Vector2::new(1.0, 0.0)));
+    assert!(ball
+        .cast_at(
+            &at(-10.0, 6.0, 0.0),
+            Vector2::new(20.0, 0.0),
+            &at(0.0, 0.0, 0.0),
+            &wall,
+        )
+        .is_none());
+    assert!(ball
+        .cast_at(
+            &at(-10.0, 0.0, 0.0),
+            Vector2::new(-20.0, 0.0),
+            &at(0.0, 0.0, 0.0),
+            &wall,
+        )
+        .is_none());
+
+    let (toi, _) = ball
+        .cast_at(
+            &at(0.2, 0.0, 0.0),
+            Vector2::new(5.0, 0.0),
+            &at(0.0, 0.0, 0.0),
+            &wall,
+        )
+        .unwrap();
+
+    assert_eq!(toi, 0.0);
+}
+
+#[test]
+fn shape_cast_around_corner() {
+    let square = collider(square(), Vec::new());
+    let ball = collider(Shape::circle(0.5), Vec::new());
+    let direction = Vector2::new(1.0, 1.0).normalize();
+    let (toi, manifold) = ball
+        .cast_at(
+            &at(-5.0, -5.0, 0.0),
+            direction * 10.0,
+            &at(0.0, 0.0, 0.0),
+            &square,
+        )
+        .unwrap();
+
+    assert!((toi * 10.0 - (32.0f32.sqrt() - 0.5)).abs() < 1e-2);
+    assert!(approx(manifold.normal, direction));
+    assert!(approx(manifold.points[0], Vector2::new(-1.0, -1.0)));
+}
+
+#[test]
+fn contains_point() {
+    let square = collider(square(), Vec::new());
+    let capsule = collider(Shape::capsule(4.0, 0.5), Vec::new());
+    let rotated = at(2.0, 0.0, std::f32::consts::FRAC_PI_4);
+
+    assert!(square.contains_at(&rotated, Vector2::new(3.3, 0.0)));
+    assert!(!square.contains_at(&rotated, Vector2::new(2.9, 0.9)));
+    assert!(capsule.contains_at(&at(0.0, 0.0, 0.0), Vector2::new(0.3, 1.9)));
+    assert!(!capsule.contains_at(&at(0.0, 0.0, 0.0), Vector2::new(0.4, 1.9)));
+}
+
+#[test]
+fn bitmask_filter_is_symmetric() {
+    let player = CollisionFilter::new(0b001, 0b110, 0);
+    let enemy = CollisionFilter::new(0b010, 0b111, 0);
+    let pickup = CollisionFilter::new(0b100, 0b001, 0);
+    let filters = [
+        player,
+        enemy,
+        pickup,
+        CollisionFilter::new(0b010, 0b111, -1),
+        CollisionFilter::new(0b010, 0b111, 2),
+        CollisionFilter::default(),
+    ];
+
+    assert!(player.passes(&enemy));
+    assert!(player.passes(&pickup));
+    assert!(!enemy.passes(&pickup));
+    assert!(!filters[3].passes(&filters[3]));
+    assert!(filters[3].passes(&filters[4]));
+    assert!(enemy.passes(&filters[3]));
+
+    for a in &filters {
+        for b in &filters {
+            assert_eq!(a.passes(b), b.passes(a));
+
+            let mut c = collider(square(), vec![0]);
+            let mut c2 = collider(square(), vec![0]);
+
+            c.filter = *a;
+            c2.filter = *b;
+
+            assert_eq!(c.interacts(0, &c2, 0), a.passes(b));
+            assert_eq!(c.interacts(0, &c2, 0), c2.interacts(0, &c, 0));
+        }
+    }
+}
+
+#[test]
+fn filters_never_replace_layers() {
+    let mut a = collider(square(), Vec::new());
+    let mut b = collider(square(), Vec::new());
+
+    a.filter = CollisionFilter::new(0b01, 0b10, 0);
+    b.filter = CollisionFilter::new(0b10, 0b01, 0);
+
+    assert!(!a.interacts(0, &b, 0));
+
+    b.layers = vec![0];
+
+    assert!(!a.interacts(0, &b, 0));
+
+    a.layers = vec![0];
+
+    assert!(a.interacts(0, &b, 0));
+    assert!(b.interacts(0, &a, 0));
+}
+
+#[test]
+fn child_and_query_filters() {
+    let mut child = Child::new(square(), Vector2::zeros(), 0.0);
+
+    child.filter = Some(CollisionFilter::new(0b10, u32::MAX, 0));
+
+    let c = collider(
+        Shape::Compound(vec![Child::new(square(), Vector2::zeros(), 0.0), child]),
+        vec![0],
+    );
+    let mut c2 = collider(square(), vec![0]);
+
+    c2.filter.mask = 0b01;
+
+    assert!(c.interacts(0, &c2, 0));
+    assert!(!c.interacts(1, &c2, 0));
+
+    let filter = QueryFilter {
+        mask: 0b10,
+        ..QueryFilter::new(vec![0], Vec::new())
+    };
+
+    assert!(!filter.matches(0, &c, 0));
+    assert!(filter.matches(0, &c, 1));
+}
+
+#[test]
+fn world_queries_refresh_after_steps() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let filter = QueryFilter::new(vec![0], Vec::new());
+    let e = spawn(
+        world,
+        Vector2::new(5.0, 0.0),
+        Collider::circle(1.0, vec![0], Vec::new(), BodyType::Static, false),
+    );
+
+    assert_eq!(
+        manager.overlap_point(world, Vector2::new(5.0, 0.0), &filter),
+        vec![e]
+    );
+
+    world
+        .em
+        .read()
+        .get_component::<Trans>(e)
+        .unwrap()
+        .write()
+        .set_position(Vector2::new(-5.0, 0.0));
+    manager.step(world);
+
+    assert!(manager
+        .overlap_point(world, Vector2::new(5.0, 0.0), &filter)
+        .is_empty());
+    assert_eq!(
+        manager.overlap_point(world, Vector2::new(-5.0, 0.0), &filter),
+        vec![e]
+    );
+}
+
+#[test]
+fn world_raycast() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let square = spawn(
+        world,
+        Vector2::new(5.0, 0.0),
+        Collider::rect(
+            Vector2::new(2.0, 2.0),
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    let circle = spawn(
+        world,
+        Vector2::new(10.0, 0.0),
+        Collider::circle(1.0, vec![1], Vec::new(), BodyType::Static, false),
+    );
+    let right = Vector2::new(1.0, 0.0);
+    let all = QueryFilter::default();
+
+    manager.step(world);
+
+    let hit = manager
+        .raycast(world, Vector2::zeros(), right * 2.0, 20.0, &all)
+        .unwrap();
+
+    assert_eq!(hit.entity, square);
+    assert!((hit.distance - 4.0).abs() < 1e-4);
+    assert!((hit.fraction.unwrap() - 0.2).abs() < 1e-4);
+    assert_eq!(
+        manager
+            .raycast_all(world, Vector2::zeros(), right, 20.0, &all)
+            .iter()
+            .map(|hit| hit.entity)
+            .collect::<Vec<_>>(),
+        vec![square, circle]
+    );
+
+    let hit = manager
+        .raycast(
+            world,
+            Vector2::zeros(),
+            right,
+            20.0,
+            &QueryFilter::new(vec![1], Vec::new()),
+        )
+        .unwrap();
+
+    assert_eq!(hit.entity, circle);
+    assert!(approx(hit.point, Vector2::new(9.0, 0.0)));
+    assert!(manager
+        .raycast(
+            world,
+            Vector2::zeros(),
+            right,
+            20.0,
+            &QueryFilter {
+                exclude: vec![square, circle],
+                ..QueryFilter::default()
+            },
+        )
+        .is_none());
+}
+
+#[test]
+fn unbounded_and_empty_rays() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let all = QueryFilter::default();
+
+    spawn(
+        world,
+        Vector2::new(0.0, 500.0),
+        Collider::circle(1.0, vec![0], Vec::new(), BodyType::Static, false),
+    );
+    manager.step(world);
+
+    let hit = manager
+        .raycast(
+            world,
+            Vector2::zeros(),
+            Vector2::new(0.0, 1.0),
+            f32::INFINITY,
+            &all,
+        )
+        .unwrap();
+
+    assert!((hit.distance - 499.0).abs() < 1e-3);
+    assert_eq!(hit.fraction, None);
+    assert!(manager
+        .raycast(
+            world,
+            Vector2::zeros(),
+            Vector2::new(1.0, 0.0),
+            f32::INFINITY,
+            &all
+        )
+        .is_none());
+
+    for max_distance in [0.0, -1.0, f32::NAN] {
+        assert!(manager
+            .raycast_all(
+                world,
+                Vector2::new(0.0, 500.0),
+                Vector2::new(0.0, 1.0),
+                max_distance,
+                &all
+            )
+            .is_empty());
+    }
+}
+
+#[test]
+fn default_filter_matches_everything() {
+    let c = collider(square(), vec![3]);
+    let bare = collider(square(), Vec::new());
+
+    assert!(QueryFilter::default().matches(0, &c, 0));
+    assert!(QueryFilter::default().matches(0, &bare, 0));
+    assert!(!QueryFilter::new(vec![1], Vec::new()).matches(0, &c, 0));
+    assert!(!QueryFilter::new(Vec::new(), vec![3]).matches(0, &c, 0));
+}
+
+#[test]
+fn world_shape_cast() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let near = spawn(
+        world,
+        Vector2::new(5.0, 0.0),
+        Collider::rect(
+            Vector2::new(2.0, 2.0),
+            vec![1],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    let far = spawn(
+        world,
+        Vector2::new(10.0, 0.0),
+        Collider::rect(
+            Vector2::new(2.0, 2.0),
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    let ball = Collider::circle(0.5, vec![0, 1], Vec::new(), BodyType::Dynamic, false);
+    let ball = ball.read();
+    let sweep = Vector2::new(20.0, 0.0);
+
+    manager.step(world);
+
+    let hit = manager
+        .shape_cast(
+            world,
+            &ball,
+            &at(0.0, 0.0, 0.0),
+            sweep,
+            &QueryFilter::default(),
+        )
+        .unwrap();
+
+    assert_eq!(hit.entity, near);
+    assert!((hit.fraction - 3.5 / 20.0).abs() < 1e-3);
+    assert!(approx(hit.point, Vector2::new(4.0, 0.0)));
+
+    let only_far = QueryFilter::new(vec![0], Vec::new());
+    let hit = manager
+        .shape_cast(world, &ball, &at(0.0, 0.0, 0.0), sweep, &only_far)
+        .unwrap();
+
+    assert_eq!(hit.entity, far);
+    assert!((hit.fraction - 8.5 / 20.0).abs() < 1e-3);
+    assert!((hit.normal - Vector2::new(-1.0, 0.0)).magnitude() < 1e-3);
+    assert!(manager
+        .shape_cast(
+            world,
+            &ball,
+            &at(0.0, 0.0, 0.0),
+            sweep,
+            &QueryFilter {
+                exclude: vec![far],
+                ..only_far.clone()
+            },
+        )
+        .is_none());
+
+    // The cast collider's own layers apply on top of the filter.
+    let mut near_only = ball.clone();
+
+    near_only.layers = vec![1];
+
+    assert!(manager
+        .shape_cast(world, &near_only, &at(0.0, 0.0, 0.0), sweep, &only_far)
+        .is_none());
+
+    near_only.ignore = vec![1];
+
+    assert!(manager
+        .shape_cast(
+            world,
+            &near_only,
+            &at(0.0, 0.0, 0.0),
+            sweep,
+            &QueryFilter::default()
+        )
+        .is_none());
+}
+
+#[test]
+fn world_overlaps() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::zeros());
+    let square = spawn(
+        world,
+        Vector2::new(5.0, 0.0),
+        Collider::rect(
+            Vector2::new(2.0, 2.0),
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+    let circle = spawn(
+        world,
+        Vector2::new(10.0, 0.0),
+        Collider::circle(1.0, vec![1], Vec::new(), BodyType::Static, false),
+    );
+    let sensor = spawn(
+        world,
+        Vector2::new(10.0, 0.0),
+        Collider::circle(3.0, vec![0], Vec::new(), BodyType::Static, true),
+    );
+    let all = QueryFilter::default();
+
+    manager.step(world);
+
+    assert_eq!(
+        manager.overlap_point(world, Vector2::new(10.5, 0.0), &all),
+        vec![circle, sensor]
+    );
+    assert_eq!(
+        manager.overlap_point(
+            world,
+            Vector2::new(10.5, 0.0),
+            &QueryFilter::new(vec![1], Vec::new())
+        ),
+        vec![circle]
+    );
+    assert_eq!(
+        manager.overlap_point(world, Vector2::new(5.5, 0.5), &all),
+        vec![square]
+    );
+    assert!(manager
+        .overlap_point(world, Vector2::new(5.0, 1.5), &all)
+        .is_empty());
+
+    let aabb = Aabb::new(Vector2::new(5.5, -0.1), Vector2::new(9.5, 0.1));
+
+    assert_eq!(
+        manager.overlap_aabb(world, &aabb, &all),
+        vec![square, circle, sensor]
+    );
+    assert_eq!(
+        manager.overlap_aabb(
+            world,
+            &aabb,
+            &QueryFilter {
+                exclude: vec![sensor],
+                ..QueryFilter::default()
+            }
+        ),
+        vec![square, circle]
+    );
+    assert_eq!(
+        manager.overlap_circle(world, Vector2::new(3.5, 0.0), 0.6, &all),
+        vec![square]
+    );
+    assert!(manager
+        .overlap_circle(world, Vector2::new(3.0, 3.0), 0.6, &all)
+        .is_empty());
+}
+
+#[test]
+fn overlap_collider_uses_the_narrowphase() {
+    let world = world();
+    let world = &*world.read();
+    let all = QueryFilter::default();
+    let e = spawn(
+        world,
+        Vector2::zeros(),
+        Collider::circle(1.0, vec![0], Vec::new(), BodyType::Static, false),
+    );
+    let probe = Collider::rect(
+        Vector2::new(1.0, 1.0),
+        Vec::new(),
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    );
+    let probe = probe.read();
+
+    for narrowphase in [Narrowphase::Sat, Narrowphase::Gjk] {
+        let mut manager = manager(Vector2::zeros());
+
+        manager.narrowphase = narrowphase;
+        manager.step(world);
+
+        assert_eq!(
+            manager.overlap_collider(world, &probe, &at(1.2, 0.0, 0.0), &all),
+            vec![e]
+        );
+        // The corner is outside the circle even though the bounds overlap.
+        assert!(manager
+            .overlap_collider(world, &probe, &at(1.3, 1.3, 0.0), &all)
+            .is_empty());
+    }
+}
+
+#[test]
+fn overlaps_ignore_one_sidedness() {
+    let world = world();
+    let world = &*world.read();
+    let all = QueryFilter::default();
+    let ground = spawn(
+        world,
+        Vector2::zeros(),
+        Collider::chain(
+            vec![
+                Vector2::new(-4.0, 0.0),
+                Vector2::new(0.0, 0.0),
+                Vector2::new(4.0, 0.0),
+            ],
+            false,
+            vec![0],
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+    );
+
+    for narrowphase in [Narrowphase::Sat, Narrowphase::Gjk] {
+        let mut manager = manager(Vector2::zeros());
+
+        manager.narrowphase = narrowphase;
+
+        // Probes behind the chain still overlap it.
+        assert_eq!(
+            manager.overlap_circle(world, Vector2::new(1.0, -0.3), 0.5, &all),
+            vec![ground]
+        );
+        assert!(manager
+            .overlap_aabb(
+                world,
+                &Aabb::new(Vector2::new(-2.0, -1.0), Vector2::new(-1.0, -0.2)),
+                &all
+            )
+            .is_empty());
+        assert_eq!(
+            manager.overlap_aabb(
+                world,
+                &Aabb::new(Vector2::new(-2.0, -1.0), Vector2::new(-1.0, 0.2)),
+                &all
+            ),
+            vec![ground]
+        );
+    }
+}
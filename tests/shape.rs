@@ -0,0 +1,191 @@
+use hex::nalgebra::{Matrix3, Vector2};
+use hex_physics::{
+    components::{BodyType, Collider},
+    shape::{Shape, ShapeError},
+};
+
+fn points(points: &[(f32, f32)]) -> Vec<Vector2<f32>> {
+    points.iter().map(|(x, y)| Vector2::new(*x, *y)).collect()
+}
+
+fn area(points: &[Vector2<f32>]) -> f32 {
+    (0..points.len())
+        .map(|i| points[i].perp(&points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        / 2.0
+}
+
+fn pieces(shape: &Shape) -> Vec<Vec<Vector2<f32>>> {
+    match shape {
+        Shape::Compound(children) => children
+            .iter()
+            .map(|c| match &c.shape {
+                Shape::Polygon(points) => points.clone(),
+                _ => panic!("expected polygon piece"),
+            })
+            .collect(),
+        _ => panic!("expected compound"),
+    }
+}
+
+fn convex(points: &[Vector2<f32>]) -> bool {
+    let len = points.len();
+
+    (0..len).all(|i| {
+        let (a, b, c) = (
+            points[(i + len - 1) % len],
+            points[i],
+            points[(i + 1) % len],
+        );
+
+        (b - a).perp(&(c - b)) >= -1e-5
+    })
+}
+
+fn l_shape() -> Vec<Vector2<f32>> {
+    points(&[
+        (0.0, 0.0),
+        (3.0, 0.0),
+        (3.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 3.0),
+        (0.0, 3.0),
+    ])
+}
+
+#[test]
+fn polygon_validation() {
+    assert!(Shape::polygon(points(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)])).is_ok());
+    assert!(Shape::polygon(points(&[(0.0, 0.0), (0.0, 1.0), (1.0, 0.0)])).is_ok());
+    assert_eq!(
+        Shape::polygon(points(&[(0.0, 0.0), (1.0, 0.0)])),
+        Err(ShapeError::TooFewPoints)
+    );
+    assert_eq!(
+        Shape::polygon(points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)])),
+        Err(ShapeError::Degenerate)
+    );
+    assert_eq!(Shape::polygon(l_shape()), Err(ShapeError::Concave));
+    assert_eq!(
+        Shape::polygon(points(&[(0.0, 0.0), (1.0, 1.0), (1.0, 0.0), (0.0, 1.0)])),
+        Err(ShapeError::SelfIntersecting)
+    );
+}
+
+#[test]
+fn concave_decomposes_into_convex_pieces() {
+    for outline in [l_shape(), l_shape().into_iter().rev().collect()] {
+        let shape = Shape::concave(outline.clone()).unwrap();
+        let pieces = pieces(&shape);
+
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces.iter().all(|p| convex(p)));
+        assert!((pieces.iter().map(|p| area(p)).sum::<f32>() - area(&outline).abs()).abs() < 1e-4);
+    }
+
+    let comb = points(&[
+        (0.0, 0.0),
+        (5.0, 0.0),
+        (5.0, 3.0),
+        (4.0, 3.0),
+        (4.0, 1.0),
+        (3.0, 1.0),
+        (3.0, 3.0),
+        (2.0, 3.0),
+        (2.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 3.0),
+        (0.0, 3.0),
+    ]);
+    let pieces = pieces(&Shape::concave(comb.clone()).unwrap());
+
+    assert!(pieces.iter().all(|p| convex(p)));
+    assert!((pieces.iter().map(|p| area(p)).sum::<f32>() - area(&comb)).abs() < 1e-4);
+    assert!(pieces.len() <= 4 * 2);
+}
+
+#[test]
+fn concave_collider_respects_notch() {
+    let l = Collider::concave(l_shape(), Vec::new(), Vec::new(), BodyType::Static, false)
+        .unwrap()
+        .read()
+        .clone();
+    let ball = Collider::circle(0.4, Vec::new(), Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone();
+    let at = |x: f32, y: f32| Matrix3::new_translation(&Vector2::new(x, y));
+
+    assert!(l.contact_at(&at(0.0, 0.0), &at(2.0, 2.0), &ball).is_none());
+    assert!(l.contact_at(&at(0.0, 0.0), &at(2.0, 1.2), &ball).is_some());
+    assert!(l.contact_at(&at(0.0, 0.0), &at(0.5, 2.5), &ball).is_some());
+    assert!(matches!(
+        Collider::concave(
+            points(&[(0.0, 0.0), (1.0, 1.0), (1.0, 0.0), (0.0, 1.0)]),
+            Vec::new(),
+            Vec::new(),
+            BodyType::Static,
+            false,
+        ),
+        Err(ShapeError::SelfIntersecting)
+    ));
+}
+
+#[test]
+fn convex_hull_of_point_cloud() {
+    let cloud = points(&[
+        (0.0, 0.0),
+        (2.0, 2.0),
+        (1.0, 1.0),
+        (2.0, 0.0),
+        (0.0, 2.0),
+        (1.0, 0.0),
+        (0.5, 1.5),
+        (2.0, 0.0),
+    ]);
+    let hull = match Shape::convex_hull(&cloud).unwrap() {
+        Shape::Polygon(points) => points,
+        _ => panic!("expected polygon"),
+    };
+
+    assert_eq!(hull.len(), 4);
+    assert!(area(&hull) > 0.0);
+    assert!((area(&hull) - 4.0).abs() < 1e-4);
+    assert!(convex(&hull));
+
+    let collider =
+        Collider::convex_hull(&cloud, Vec::new(), Vec::new(), BodyType::Dynamic, false).unwrap();
+
+    assert!((collider.read().boundary() - 8.0f32.sqrt()).abs() < 1e-4);
+    assert_eq!(
+        Shape::convex_hull(&points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)])),
+        Err(ShapeError::Degenerate)
+    );
+    assert_eq!(
+        Shape::convex_hull(&points(&[(0.0, 0.0), (1.0, 1.0)])),
+        Err(ShapeError::TooFewPoints)
+    );
+}
+
+#[test]
+fn boundary_is_tight() {
+    let rect = Collider::rect(
+        Vector2::new(2.0, 4.0),
+        Vec::new(),
+        Vec::new(),
+        BodyType::Dynamic,
+        false,
+    );
+    let capsule = Collider::capsule(4.0, 1.0, Vec::new(), Vec::new(), BodyType::Dynamic, false);
+    let scaled = Matrix3::new_translation(&Vector2::new(5.0, 5.0))
+        * Matrix3::new_nonuniform_scaling(&Vector2::new(3.0, 1.0));
+
+    assert!((rect.read().boundary() - 5.0f32.sqrt()).abs() < 1e-4);
+    assert!((capsule.read().boundary() - 2.0).abs() < 1e-4);
+    assert!((rect.read().boundary_at(&scaled) - 13.0f32.sqrt()).abs() < 1e-4);
+    assert!((capsule.read().boundary_at(&scaled) - 4.0).abs() < 1e-4);
+
+    let aabb = rect.read().aabb_at(&scaled).unwrap();
+
+    assert_eq!(aabb.min, Vector2::new(2.0, 3.0));
+    assert_eq!(aabb.max, Vector2::new(8.0, 7.0));
+}
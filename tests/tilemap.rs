@@ -0,0 +1,240 @@
+mod common;
+
+use common::{manager, rect, spawn, spawn_body, step, world};
+use hex::nalgebra::{Matrix3, Vector2};
+use hex_physics::{
+    aabb::Aabb,
+    components::{BodyType, Collider},
+    event::CollisionState,
+    tilemap::Tilemap,
+};
+
+fn grid(rows: &[&str]) -> Vec<Vec<bool>> {
+    rows.iter()
+        .rev()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect()
+}
+
+fn rects(tilemap: &Tilemap) -> Vec<Aabb> {
+    tilemap.rects().map(|(_, r)| r).collect()
+}
+
+fn area(tilemap: &Tilemap) -> f32 {
+    tilemap
+        .rects()
+        .map(|(_, r)| r.extents().x * r.extents().y)
+        .sum()
+}
+
+// Every solid tile is covered by exactly one rectangle and nothing else is.
+fn covers_exactly(tilemap: &Tilemap) -> bool {
+    let size = tilemap.tile_size;
+
+    (0..tilemap.height()).all(|y| {
+        (0..tilemap.width()).all(|x| {
+            let center = Vector2::new(x as f32 + 0.5, y as f32 + 0.5).component_mul(&size);
+            let covering = tilemap
+                .rects()
+                .filter(|(_, r)| r.contains_point(&center))
+                .count();
+
+            covering == tilemap.get(x, y) as usize
+        })
+    })
+}
+
+#[test]
+fn merges_runs_into_rects() {
+    let tilemap = Tilemap::from_grid(
+        &grid(&["#....#", "#....#", "######", "######"]),
+        Vector2::new(1.0, 1.0),
+    );
+
+    assert_eq!(rects(&tilemap).len(), 3);
+    assert!(rects(&tilemap).contains(&Aabb::new(Vector2::new(0.0, 0.0), Vector2::new(6.0, 2.0))));
+    assert!(rects(&tilemap).contains(&Aabb::new(Vector2::new(5.0, 2.0), Vector2::new(6.0, 4.0))));
+    assert_eq!(area(&tilemap), 16.0);
+}
+
+#[test]
+fn incremental_updates_match_rebuild() {
+    let rows = ["..##..", "######", "#.##.#"];
+    let mut tilemap = Tilemap::new(6, 3, Vector2::new(0.5, 0.5));
+
+    for (y, row) in grid(&rows).iter().enumerate() {
+        for (x, solid) in row.iter().enumerate() {
+            tilemap.set(x, y, *solid);
+        }
+    }
+
+    let rebuilt = Tilemap::from_grid(&grid(&rows), Vector2::new(0.5, 0.5));
+
+    assert_eq!(rects(&tilemap).len(), rects(&rebuilt).len());
+    assert!(covers_exactly(&tilemap));
+    assert!(!tilemap.set(2, 2, true));
+    assert!(!tilemap.set(10, 0, true));
+    assert!(tilemap.set(2, 2, false));
+    assert!(!tilemap.get(2, 2));
+    assert!(covers_exactly(&tilemap));
+    assert_eq!(area(&tilemap), 11.0 * 0.25);
+}
+
+#[test]
+fn tilemap_collider_tracks_edits() {
+    let collider = Collider::tilemap(
+        Tilemap::from_grid(&grid(&["....", "####"]), Vector2::new(1.0, 1.0)),
+        Vec::new(),
+        Vec::new(),
+        BodyType::Static,
+        false,
+    );
+    let ball = Collider::circle(0.5, Vec::new(), Vec::new(), BodyType::Dynamic, false)
+        .read()
+        .clone();
+    let at = |x: f32, y: f32| Matrix3::new_translation(&Vector2::new(x, y));
+    let manifold = collider
+        .read()
+        .contact_at(&at(0.0, 0.0), &at(1.5, 1.4), &ball)
+        .unwrap();
+
+    assert!((manifold.normal - Vector2::new(0.0, 1.0)).magnitude() < 1e-4);
+    assert!((manifold.depth - 0.1).abs() < 1e-4);
+
+    collider.write().tilemap_mut().unwrap().set(1, 0, false);
+
+    assert!(collider
+        .read()
+        .contact_at(&at(0.0, 0.0), &at(1.5, 1.4), &ball)
+        .is_none());
+
+    collider.write().tilemap_mut().unwrap().set(1, 0, true);
+
+    assert_eq!(collider.write().tilemap_mut().unwrap().rects().count(), 1);
+    assert!(collider
+        .read()
+        .contact_at(&at(0.0, 0.0), &at(1.5, 1.4), &ball)
+        .is_some());
+}
+
+#[test]
+fn edits_keep_other_indices() {
+    let mut tilemap = Tilemap::from_grid(
+        &grid(&["#......#", "#......#", "########"]),
+        Vector2::new(1.0, 1.0),
+    );
+    let before = rects(&tilemap);
+    let indexed: Vec<_> = tilemap.rects().collect();
+
+    tilemap.set(7, 2, false);
+
+    // The left wall and the floor keep their rectangles and indices.
+    for (i, rect) in &indexed {
+        if rect.min.x < 5.0 {
+            assert!(tilemap.rects().any(|(j, r)| j == *i && r == *rect));
+        }
+    }
+
+    tilemap.set(7, 2, true);
+
+    assert_eq!(rects(&tilemap), before);
+    assert_eq!(tilemap.rects().collect::<Vec<_>>(), indexed);
+
+    // Freed indices are reused before new ones are added.
+    tilemap.set(3, 1, true);
+    tilemap.set(3, 1, false);
+
+    assert!(covers_exactly(&tilemap));
+    assert!(tilemap.rects().all(|(i, _)| i < indexed.len() + 1));
+}
+
+#[test]
+fn random_edits_stay_exact() {
+    let mut tilemap = Tilemap::new(12, 9, Vector2::new(0.5, 2.0));
+    let mut seed = 7u32;
+
+    for _ in 0..400 {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+
+        let (x, y) = ((seed >> 8) as usize % 12, (seed >> 16) as usize % 9);
+
+        tilemap.set(x, y, seed >> 28 < 10);
+
+        assert!(covers_exactly(&tilemap));
+    }
+
+    let solid = (0..9)
+        .flat_map(|y| (0..12).map(move |x| (x, y)))
+        .filter(|(x, y)| tilemap.get(*x, *y))
+        .count();
+
+    assert!((area(&tilemap) - solid as f32).abs() < 1e-3);
+}
+
+#[test]
+fn rects_in_only_visits_nearby_tiles() {
+    let tilemap = Tilemap::from_grid(&grid(&["#.#.#.#.", "########"]), Vector2::new(1.0, 1.0));
+    let near = tilemap.rects_in(&Aabb::new(Vector2::new(2.2, 1.2), Vector2::new(2.8, 1.8)));
+
+    assert_eq!(near.len(), 1);
+    assert_eq!(
+        near[0].1,
+        Aabb::new(Vector2::new(2.0, 1.0), Vector2::new(3.0, 2.0))
+    );
+    assert_eq!(
+        tilemap
+            .rects_in(&Aabb::new(Vector2::new(-1.0, -1.0), Vector2::new(9.0, 0.5)))
+            .len(),
+        1
+    );
+    assert!(tilemap
+        .rects_in(&Aabb::new(Vector2::new(1.2, 1.2), Vector2::new(1.8, 5.0)))
+        .is_empty());
+    assert!(tilemap
+        .rects_in(&Aabb::new(Vector2::new(5.0, 1.5), Vector2::new(1.0, 0.5)))
+        .is_empty());
+    assert!(tilemap
+        .rects_in(&Aabb::new(
+            Vector2::new(1.0, f32::NAN),
+            Vector2::new(f32::NAN, 1.5)
+        ))
+        .is_empty());
+}
+
+#[test]
+fn distant_edits_leave_resting_contacts_alone() {
+    let world = world();
+    let world = &*world.read();
+    let mut manager = manager(Vector2::new(0.0, -10.0));
+    let level = Collider::tilemap(
+        Tilemap::from_grid(
+            &grid(&["####........", "............", "...........#"]),
+            Vector2::new(1.0, 1.0),
+        ),
+        vec![0],
+        Vec::new(),
+        BodyType::Static,
+        false,
+    );
+
+    spawn(world, Vector2::zeros(), level.clone());
+    spawn_body(
+        world,
+        Vector2::new(2.0, 3.5),
+        rect(1.0, 1.0, BodyType::Dynamic),
+        1.0,
+    );
+    step(&mut manager, world, 60);
+
+    // Each edit adds a rectangle below the platform the body rests on.
+    for x in [5, 7] {
+        level.write().tilemap_mut().unwrap().set(x, 0, true);
+        manager.drain_events();
+        step(&mut manager, world, 1);
+
+        let events = manager.events.read();
+
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|e| e.state == CollisionState::Persisted));
+    }
+}